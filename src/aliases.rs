@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+
+use crate::autocomplete::normalize;
+
+/// Alternative names for Pals, read from the JSON object of alias to Pal name at
+/// `PAL_ALIASES_PATH`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(from = "HashMap<String, String>")]
+pub struct Aliases(HashMap<String, String>);
+
+impl From<HashMap<String, String>> for Aliases {
+    fn from(aliases: HashMap<String, String>) -> Self {
+        Self(
+            aliases
+                .into_iter()
+                .map(|(alias, name)| (normalize(alias.trim()), name))
+                .collect(),
+        )
+    }
+}
+
+impl Aliases {
+    /// The Pal name `alias` stands for, matched regardless of case and accents.
+    pub fn resolve(&self, alias: &str) -> Option<&str> {
+        self.0.get(&normalize(alias.trim())).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let aliases: Aliases =
+            serde_json::from_str(r#"{"Sheep": "Lamball", " jormu ": "Jormuntide"}"#).unwrap();
+
+        assert_eq!(aliases.resolve("sheep"), Some("Lamball"));
+        assert_eq!(aliases.resolve("JORMU"), Some("Jormuntide"));
+        assert_eq!(aliases.resolve("Lamball"), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::autocomplete::normalize;
+
+/// How much of their letters two names must share for `/anagram` to list them.
+pub const MIN_ANAGRAM_SCORE: f64 = 0.6;
+
+/// How many of each letter `text` has, ignoring case, diacritics, spaces and punctuation.
+fn letters(text: &str) -> HashMap<char, usize> {
+    let mut counts = HashMap::new();
+    for c in normalize(text).chars().filter(|c| c.is_alphanumeric()) {
+        *counts.entry(c).or_default() += 1;
+    }
+    counts
+}
+
+/// How close `a` and `b` are to being anagrams, from 0 for no letters in common to 1 for exactly
+/// the same letters: twice the letters they share, over how many letters they have between them.
+pub fn anagram_score(a: &str, b: &str) -> f64 {
+    let (a, b) = (letters(a), letters(b));
+    let total = a.values().sum::<usize>() + b.values().sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let shared = a
+        .iter()
+        .map(|(c, count)| (*count).min(b.get(c).copied().unwrap_or(0)))
+        .sum::<usize>();
+    2.0 * shared as f64 / total as f64
+}
+
+/// The `n` names scoring at least [`MIN_ANAGRAM_SCORE`] against `query`, best first and then
+/// alphabetically, with their scores.
+pub fn best_anagrams<'a>(names: &'a [String], query: &str, n: usize) -> Vec<(&'a str, f64)> {
+    let mut scored = names
+        .iter()
+        .map(|name| (name.as_str(), anagram_score(name, query)))
+        .filter(|(_, score)| *score >= MIN_ANAGRAM_SCORE)
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    scored.truncate(n);
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anagram_score() {
+        assert_eq!(anagram_score("Lamball", "Ball Lam"), 1.0);
+        assert_eq!(anagram_score("Cattiva", "a vict at"), 1.0);
+        assert_eq!(anagram_score("Lamball", "lamb"), 8.0 / 11.0);
+        assert_eq!(anagram_score("Lamball", "Zoe"), 0.0);
+        assert_eq!(anagram_score("", "!!"), 0.0);
+    }
+
+    #[test]
+    fn test_best_anagrams() {
+        let names = ["Lamball", "Cattiva", "Tanzee", "Lamball Cryst"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+
+        let best = best_anagrams(&names, "llabmal", 5);
+        assert_eq!(best[0], ("Lamball", 1.0));
+        assert_eq!(best.len(), 2);
+        assert_eq!(best[1].0, "Lamball Cryst");
+        assert!(best_anagrams(&names, "xyz", 5).is_empty());
+    }
+}
@@ -1,24 +1,195 @@
-use simsearch::SimSearch;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use simsearch::{SearchOptions, SimSearch};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Something that can suggest names matching a partial query.
+pub trait Completer: Send + Sync {
+    /// Returns at most `limit` names matching `query`, best match first.
+    fn search(&self, query: &str, limit: usize) -> Vec<String>;
+}
+
+/// The autocomplete backends selectable with `AUTOCOMPLETE_BACKEND`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Fuzzy matching with `simsearch`.
+    #[default]
+    SimSearch,
+    /// Case-insensitive prefix matching.
+    Prefix,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simsearch" | "fuzzy" => Ok(Self::SimSearch),
+            "prefix" => Ok(Self::Prefix),
+            other => Err(format!("Unknown autocomplete backend `{other}`")),
+        }
+    }
+}
+
+impl Backend {
+    /// Builds a completer of this kind over `data`. `threshold` and `prefix_weight` only apply to
+    /// fuzzy matching.
+    pub fn build(self, data: &[String], threshold: f64, prefix_weight: f64) -> Box<dyn Completer> {
+        match self {
+            Self::SimSearch => Box::new(
+                AutoCompleteEngine::with_threshold(data, threshold).prefix_weight(prefix_weight),
+            ),
+            Self::Prefix => Box::new(PrefixEngine::new(data)),
+        }
+    }
+}
+
+/// Lowercases `text` and strips diacritics, so "Élan" and "elan" compare equal.
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// How similar, from 0 to 1, a name must be to a query for fuzzy matching to suggest it, unless
+/// configured otherwise. This is `simsearch`'s own default.
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// How much starting with the query counts for when ranking fuzzy matches, unless configured
+/// otherwise. At 1 or more, every name starting with the query ranks above every name that
+/// doesn't.
+pub const DEFAULT_PREFIX_WEIGHT: f64 = 1.0;
 
 /// A simple autocomplete engine that uses the `simsearch` crate.
 pub struct AutoCompleteEngine {
     engine: SimSearch<String>,
+    originals: HashMap<String, String>,
+    prefix_weight: f64,
 }
 
 impl AutoCompleteEngine {
-    /// Create a new `AutoCompleteEngine` with the given data.
+    /// Create a new `AutoCompleteEngine` with the given data, using [`DEFAULT_THRESHOLD`].
+    #[cfg(test)]
     pub fn new(data: &[String]) -> Self {
-        let mut engine = SimSearch::new();
+        Self::with_threshold(data, DEFAULT_THRESHOLD)
+    }
+
+    /// Create a new `AutoCompleteEngine` with the given data, suggesting only names more similar
+    /// than `threshold` to the query. Higher thresholds are stricter.
+    pub fn with_threshold(data: &[String], threshold: f64) -> Self {
+        let mut engine = SimSearch::new_with(SearchOptions::new().threshold(threshold));
+        let mut originals = HashMap::new();
 
         for name in data {
-            engine.insert(name.clone(), name);
+            let key = normalize(name);
+            engine.insert(key.clone(), &key);
+            originals.insert(key, name.clone());
+        }
+
+        Self {
+            engine,
+            originals,
+            prefix_weight: DEFAULT_PREFIX_WEIGHT,
         }
+    }
 
-        Self { engine }
+    /// Weights names starting with the query by `weight` when ranking them. See [`rerank`].
+    pub fn prefix_weight(self, weight: f64) -> Self {
+        Self {
+            prefix_weight: weight,
+            ..self
+        }
     }
 
-    pub fn autocomplete(&self, query: &str) -> Vec<String> {
-        self.engine.search(query)
+    /// The names most similar to `query`, with those starting with it boosted.
+    pub fn autocomplete(&self, query: &str, limit: usize) -> Vec<String> {
+        let query = normalize(query);
+        rerank(self.engine.search(&query), &query, self.prefix_weight)
+            .iter()
+            .filter_map(|key| self.originals.get(key).cloned())
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Reorders the normalized `keys`, best fuzzy match first, by a score combining the two.
+///
+/// `simsearch` doesn't expose its scores, so each key's fuzzy score is its position in `keys`,
+/// from 1 for the first down towards 0 for the last. Keys starting with `query` add
+/// `prefix_weight` to that. Ties keep their fuzzy order.
+fn rerank(keys: Vec<String>, query: &str, prefix_weight: f64) -> Vec<String> {
+    let count = keys.len() as f64;
+    let mut scored = keys
+        .into_iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let fuzzy = 1.0 - i as f64 / count;
+            let bonus = if key.starts_with(query) {
+                prefix_weight
+            } else {
+                0.0
+            };
+            (fuzzy + bonus, key)
+        })
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().map(|(_, key)| key).collect()
+}
+
+/// The `names` containing `query`, ignoring case and diacritics, with those starting with it
+/// first. This is the crude fallback for when an engine can't answer.
+pub fn substring_matches(names: &[String], query: &str, limit: usize) -> Vec<String> {
+    let query = normalize(query);
+    let (prefixed, rest): (Vec<_>, Vec<_>) = names
+        .iter()
+        .map(|name| (normalize(name), name))
+        .filter(|(key, _)| key.contains(&query))
+        .partition(|(key, _)| key.starts_with(&query));
+
+    prefixed
+        .into_iter()
+        .chain(rest)
+        .map(|(_, name)| name.clone())
+        .take(limit)
+        .collect()
+}
+
+impl Completer for AutoCompleteEngine {
+    fn search(&self, query: &str, limit: usize) -> Vec<String> {
+        self.autocomplete(query, limit)
+    }
+}
+
+/// An autocomplete engine that matches names starting with the query, in alphabetical order.
+pub struct PrefixEngine {
+    names: Vec<(String, String)>,
+}
+
+impl PrefixEngine {
+    /// Create a new `PrefixEngine` with the given data.
+    pub fn new(data: &[String]) -> Self {
+        let mut names = data
+            .iter()
+            .map(|name| (normalize(name), name.clone()))
+            .collect::<Vec<_>>();
+        names.sort();
+
+        Self { names }
+    }
+}
+
+impl Completer for PrefixEngine {
+    fn search(&self, query: &str, limit: usize) -> Vec<String> {
+        let query = normalize(query);
+        self.names
+            .iter()
+            .filter(|(key, _)| key.starts_with(&query))
+            .map(|(_, name)| name.clone())
+            .take(limit)
+            .collect()
     }
 }
 
@@ -36,7 +207,144 @@ mod tests {
 
         let ac = AutoCompleteEngine::new(&names);
 
-        assert_eq!(ac.autocomplete("appl"), vec!["Apple"]);
-        assert_eq!(ac.autocomplete("ap"), vec!["Apex", "Apple"]);
+        assert_eq!(ac.autocomplete("appl", 10), vec!["Apple"]);
+        assert_eq!(ac.autocomplete("ap", 10), vec!["Apex", "Apple"]);
+        assert_eq!(ac.autocomplete("ap", 1), vec!["Apex"]);
+        assert!(ac.autocomplete("ap", 0).is_empty());
+    }
+
+    #[test]
+    fn test_autocomplete_ignores_case_and_diacritics() {
+        let names = vec![
+            "Lamball".to_string(),
+            "Élan".to_string(),
+            "Cattiva".to_string(),
+        ];
+
+        let ac = AutoCompleteEngine::new(&names);
+
+        assert_eq!(ac.autocomplete("LAMB", 10), vec!["Lamball"]);
+        assert_eq!(ac.autocomplete("lAmBaLl", 10), vec!["Lamball"]);
+        assert_eq!(ac.autocomplete("elan", 10), vec!["Élan"]);
+        assert_eq!(ac.autocomplete("ÉLAN", 10), vec!["Élan"]);
+        assert_eq!(PrefixEngine::new(&names).search("el", 10), vec!["Élan"]);
+    }
+
+    #[test]
+    fn test_rerank() {
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let hoisted = |k: &[&str], query| rerank(keys(k), query, DEFAULT_PREFIX_WEIGHT);
+
+        assert_eq!(
+            hoisted(&["cattiva", "lamball", "flambelle", "lifmunk"], "la"),
+            keys(&["lamball", "cattiva", "flambelle", "lifmunk"])
+        );
+        assert_eq!(
+            hoisted(&["flambelle", "lamball", "lambwool"], "lamb"),
+            keys(&["lamball", "lambwool", "flambelle"])
+        );
+        assert_eq!(hoisted(&["cattiva"], "la"), keys(&["cattiva"]));
+    }
+
+    #[test]
+    fn test_rerank_prefix_weight() {
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let fuzzy_first = keys(&["flambelle", "cattiva", "lamball"]);
+
+        assert_eq!(
+            rerank(fuzzy_first.clone(), "lam", 1.0),
+            keys(&["lamball", "flambelle", "cattiva"])
+        );
+        assert_eq!(
+            rerank(fuzzy_first.clone(), "lam", 0.4),
+            keys(&["flambelle", "lamball", "cattiva"])
+        );
+        assert_eq!(rerank(fuzzy_first.clone(), "lam", 0.0), fuzzy_first);
+    }
+
+    #[test]
+    fn test_autocomplete_prefers_prefix_matches() {
+        let names = vec![
+            "Flambelle".to_string(),
+            "Lamball".to_string(),
+            "Cattiva".to_string(),
+            "Lifmunk".to_string(),
+        ];
+
+        let ac = AutoCompleteEngine::new(&names);
+        assert_eq!(ac.autocomplete("la", 10)[0], "Lamball");
+        assert_eq!(ac.autocomplete("lamb", 1), vec!["Lamball"]);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let names = vec![
+            "Lamball".to_string(),
+            "Lambwool".to_string(),
+            "Lifmunk".to_string(),
+            "Lunaris".to_string(),
+            "Cattiva".to_string(),
+        ];
+
+        let strict = AutoCompleteEngine::with_threshold(&names, 0.9);
+        let lenient = AutoCompleteEngine::with_threshold(&names, 0.5);
+        let strict = strict.autocomplete("lamb", 10);
+        let lenient = lenient.autocomplete("lamb", 10);
+
+        assert!(strict.len() < lenient.len(), "{strict:?} vs {lenient:?}");
+        assert!(strict.iter().all(|name| lenient.contains(name)));
+        assert_eq!(
+            AutoCompleteEngine::new(&names).autocomplete("lamb", 10),
+            AutoCompleteEngine::with_threshold(&names, DEFAULT_THRESHOLD).autocomplete("lamb", 10)
+        );
+    }
+
+    #[test]
+    fn test_substring_matches() {
+        let names = vec![
+            "Flambelle".to_string(),
+            "Lamball".to_string(),
+            "Cattiva".to_string(),
+            "Élan".to_string(),
+        ];
+
+        assert_eq!(
+            substring_matches(&names, "LAM", 10),
+            vec!["Lamball", "Flambelle"]
+        );
+        assert_eq!(substring_matches(&names, "lam", 1), vec!["Lamball"]);
+        assert_eq!(substring_matches(&names, "ela", 10), vec!["Élan"]);
+        assert!(substring_matches(&names, "xyz", 10).is_empty());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Élan Vítal"), "elan vital");
+        assert_eq!(normalize("Lamball"), "lamball");
+    }
+
+    #[test]
+    fn test_prefix_backend() {
+        let names = vec![
+            "Banana".to_string(),
+            "Apple".to_string(),
+            "Apex".to_string(),
+        ];
+
+        let backend = "prefix".parse::<Backend>().unwrap();
+        assert_eq!(backend, Backend::Prefix);
+
+        let ac = backend.build(&names, DEFAULT_THRESHOLD, DEFAULT_PREFIX_WEIGHT);
+        assert_eq!(ac.search("AP", 10), vec!["Apex", "Apple"]);
+        assert_eq!(ac.search("apl", 10), Vec::<String>::new());
+        assert_eq!(ac.search("", 10), vec!["Apex", "Apple", "Banana"]);
+        assert_eq!(ac.search("", 2), vec!["Apex", "Apple"]);
+    }
+
+    #[test]
+    fn test_backend_from_str() {
+        assert_eq!("simsearch".parse::<Backend>(), Ok(Backend::SimSearch));
+        assert_eq!("Prefix".parse::<Backend>(), Ok(Backend::Prefix));
+        assert!("trie".parse::<Backend>().is_err());
     }
 }
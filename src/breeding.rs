@@ -0,0 +1,288 @@
+use std::collections::VecDeque;
+
+use crate::api::Pal;
+
+/// Whether a Pal has breeding data.
+fn breedable(pal: &Pal) -> bool {
+    pal.breeding.rank > 0
+}
+
+/// The Pal hatched from parents `a` and `b`.
+///
+/// The child is the Pal whose breeding rank is closest to the parents' average, with ties going
+/// to the lower breeding order. Parents of the same species always produce that species.
+pub fn child_of<'a>(pals: &'a [Pal], a: &Pal, b: &Pal) -> Option<&'a Pal> {
+    child_index(pals, a, b).map(|i| &pals[i])
+}
+
+/// The index in `pals` of the Pal hatched from parents `a` and `b`.
+fn child_index(pals: &[Pal], a: &Pal, b: &Pal) -> Option<usize> {
+    if !breedable(a) || !breedable(b) {
+        return None;
+    }
+
+    if a.name == b.name {
+        return pals.iter().position(|pal| pal.name == a.name);
+    }
+
+    let target = (a.breeding.rank + b.breeding.rank + 1) / 2;
+    pals.iter()
+        .enumerate()
+        .filter(|(_, pal)| breedable(pal))
+        .min_by_key(|(_, pal)| ((pal.breeding.rank - target).abs(), pal.breeding.order))
+        .map(|(i, _)| i)
+}
+
+/// For each Pal in `pals`, the indices of the Pals it is linked to by breeding, either as a
+/// parent or as a child.
+fn graph(pals: &[Pal]) -> Vec<Vec<usize>> {
+    let mut edges = vec![Vec::new(); pals.len()];
+
+    for (i, a) in pals.iter().enumerate() {
+        for (j, b) in pals.iter().enumerate().skip(i) {
+            let Some(child) = child_index(pals, a, b) else {
+                continue;
+            };
+            for parent in [i, j] {
+                if parent != child {
+                    edges[parent].push(child);
+                    edges[child].push(parent);
+                }
+            }
+        }
+    }
+
+    for neighbours in &mut edges {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+    edges
+}
+
+/// The shortest chain of Pals from `from` to `to`, where each Pal in the chain is a parent or
+/// child of the next. Returns `None` if either Pal is unknown or no chain connects them.
+pub fn chain<'a>(pals: &'a [Pal], from: &str, to: &str) -> Option<Vec<&'a Pal>> {
+    let position = |name: &str| {
+        pals.iter()
+            .position(|pal| pal.name.eq_ignore_ascii_case(name))
+    };
+    let (start, goal) = (position(from)?, position(to)?);
+
+    let edges = graph(pals);
+    let mut previous = vec![None; pals.len()];
+    let mut visited = vec![false; pals.len()];
+    let mut queue = VecDeque::from([start]);
+    visited[start] = true;
+
+    while let Some(current) = queue.pop_front() {
+        if current == goal {
+            let mut path = vec![&pals[goal]];
+            let mut at = goal;
+            while let Some(prev) = previous[at] {
+                path.push(&pals[prev]);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for &next in &edges[current] {
+            if !visited[next] {
+                visited[next] = true;
+                previous[next] = Some(current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Explains how `a` and `b` are linked, as `(parent, partner, child)`.
+pub fn link<'a>(pals: &'a [Pal], a: &'a Pal, b: &'a Pal) -> Option<(&'a Pal, &'a Pal, &'a Pal)> {
+    [(a, b), (b, a)].into_iter().find_map(|(parent, child)| {
+        pals.iter()
+            .find(|partner| child_of(pals, parent, partner).is_some_and(|c| c.name == child.name))
+            .map(|partner| (parent, partner, child))
+    })
+}
+
+/// Every unordered pair of parents in `pals` that produces `target`.
+pub fn parent_pairs<'a>(pals: &'a [Pal], target: &str) -> Vec<(&'a Pal, &'a Pal)> {
+    let mut pairs = Vec::new();
+
+    for (i, a) in pals.iter().enumerate() {
+        for b in &pals[i..] {
+            if child_of(pals, a, b).is_some_and(|child| child.name == target) {
+                pairs.push((a, b));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// The pairs of parents that produce `target` without needing one already, easiest to come by
+/// first.
+///
+/// A Pal's breeding rank stands in for how common it is, since higher ranks go to the weaker Pals
+/// found all over the world. A pair is only as easy to get as its rarer parent, so pairs are
+/// ranked by that parent's breeding rank, then by the other parent's.
+pub fn cheapest_parent_pairs<'a>(pals: &'a [Pal], target: &str) -> Vec<(&'a Pal, &'a Pal)> {
+    let mut pairs = parent_pairs(pals, target)
+        .into_iter()
+        .filter(|(a, b)| a.name != target && b.name != target)
+        .collect::<Vec<_>>();
+    let availability = |(a, b): &(&Pal, &Pal)| {
+        let (rarer, commoner) = if a.breeding.rank <= b.breeding.rank {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        (rarer.breeding.rank, commoner.breeding.rank)
+    };
+    pairs.sort_by_key(|pair| std::cmp::Reverse(availability(pair)));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Breeding;
+
+    fn pal(name: &str, rank: i64, order: i64) -> Pal {
+        Pal {
+            name: name.to_string(),
+            breeding: Breeding { rank, order },
+            ..Default::default()
+        }
+    }
+
+    fn names<'a>(pairs: &[(&'a Pal, &'a Pal)]) -> Vec<(&'a str, &'a str)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.name.as_str(), b.name.as_str()))
+            .collect()
+    }
+
+    fn sample() -> Vec<Pal> {
+        vec![
+            pal("Alpha", 100, 1),
+            pal("Bravo", 200, 2),
+            pal("Charlie", 300, 3),
+            pal("Delta", 400, 4),
+            pal("Unbreedable", 0, 5),
+        ]
+    }
+
+    #[test]
+    fn test_child_of() {
+        let pals = sample();
+
+        assert_eq!(child_of(&pals, &pals[0], &pals[2]).unwrap().name, "Bravo");
+        assert_eq!(child_of(&pals, &pals[0], &pals[0]).unwrap().name, "Alpha");
+        // (100 + 400 + 1) / 2 = 250 is equally close to Bravo and Charlie.
+        assert_eq!(child_of(&pals, &pals[0], &pals[3]).unwrap().name, "Bravo");
+        assert!(child_of(&pals, &pals[0], &pals[4]).is_none());
+    }
+
+    /// Alpha—Bravo, Bravo—Charlie, Bravo—Delta and Charlie—Delta are linked, but Alpha and Delta
+    /// only through Bravo.
+    fn spread() -> Vec<Pal> {
+        vec![
+            pal("Alpha", 100, 1),
+            pal("Bravo", 200, 2),
+            pal("Charlie", 1000, 3),
+            pal("Delta", 1100, 4),
+            pal("Unbreedable", 0, 5),
+        ]
+    }
+
+    fn chain_names(pals: &[Pal], from: &str, to: &str) -> Option<Vec<String>> {
+        chain(pals, from, to).map(|path| path.iter().map(|pal| pal.name.clone()).collect())
+    }
+
+    #[test]
+    fn test_chain() {
+        let pals = spread();
+
+        assert_eq!(
+            chain_names(&pals, "Alpha", "Bravo"),
+            Some(vec!["Alpha".to_string(), "Bravo".to_string()])
+        );
+        assert_eq!(
+            chain_names(&pals, "alpha", "DELTA"),
+            Some(vec![
+                "Alpha".to_string(),
+                "Bravo".to_string(),
+                "Delta".to_string()
+            ])
+        );
+        assert_eq!(
+            chain_names(&pals, "Charlie", "Charlie"),
+            Some(vec!["Charlie".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_chain_disconnected() {
+        let pals = spread();
+
+        assert_eq!(chain_names(&pals, "Alpha", "Unbreedable"), None);
+        assert_eq!(chain_names(&pals, "Alpha", "Zulu"), None);
+    }
+
+    #[test]
+    fn test_link() {
+        let pals = spread();
+
+        // Alpha + Charlie = Bravo.
+        let (parent, partner, child) = link(&pals, &pals[0], &pals[1]).unwrap();
+        assert_eq!(
+            (
+                parent.name.as_str(),
+                partner.name.as_str(),
+                child.name.as_str()
+            ),
+            ("Alpha", "Charlie", "Bravo")
+        );
+        assert!(link(&pals, &pals[0], &pals[4]).is_none());
+    }
+
+    #[test]
+    fn test_parent_pairs() {
+        let pals = sample();
+
+        assert_eq!(
+            names(&parent_pairs(&pals, "Bravo")),
+            vec![
+                ("Alpha", "Charlie"),
+                ("Alpha", "Delta"),
+                ("Bravo", "Bravo"),
+                ("Bravo", "Charlie"),
+            ]
+        );
+        assert!(parent_pairs(&pals, "Unbreedable").is_empty());
+    }
+
+    #[test]
+    fn test_cheapest_parent_pairs() {
+        let pals = vec![
+            pal("Rare", 100, 1),
+            pal("Uncommon", 300, 2),
+            pal("Target", 500, 3),
+            pal("Common", 700, 4),
+            pal("Plentiful", 900, 5),
+        ];
+
+        assert_eq!(
+            names(&cheapest_parent_pairs(&pals, "Target")),
+            vec![
+                ("Uncommon", "Plentiful"),
+                ("Uncommon", "Common"),
+                ("Rare", "Plentiful"),
+            ]
+        );
+        assert!(cheapest_parent_pairs(&pals, "Unknown").is_empty());
+    }
+}
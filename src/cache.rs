@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A thread-safe map whose entries expire a fixed time after insertion.
+pub struct TtlCache<K, V> {
+    entries: Mutex<HashMap<K, (V, Instant)>>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    /// Create an empty cache whose entries live for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            capacity: usize::MAX,
+        }
+    }
+
+    /// Hold at most `capacity` entries, evicting the oldest to make room.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self { capacity, ..self }
+    }
+
+    /// Returns a clone of the value for `key`, if present and not yet expired.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((value, inserted)) if inserted.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores `value` for `key`, replacing any previous entry. Expired entries are dropped first,
+    /// then the oldest if the cache is still full.
+    pub fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, inserted)| inserted.elapsed() < self.ttl);
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (_, inserted))| *inserted)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (value, Instant::now()));
+    }
+
+    /// The value for `key` if present and not yet expired, otherwise the result of `compute`,
+    /// which is stored in its place. `compute` runs with the cache unlocked, so other lookups
+    /// aren't held up by it.
+    pub fn get_or_insert_with(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+
+        let value = compute();
+        self.insert(key, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_within_ttl() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        cache.insert("lamball", 1);
+
+        assert_eq!(cache.get(&"lamball"), Some(1));
+        assert_eq!(cache.get(&"cattiva"), None);
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+
+        assert_eq!(cache.get_or_insert_with("lamb", || "Lamball"), "Lamball");
+        assert_eq!(cache.get_or_insert_with("lamb", || "Lambwool"), "Lamball");
+        assert_eq!(cache.get_or_insert_with("cat", || "Cattiva"), "Cattiva");
+
+        let expired = TtlCache::new(Duration::ZERO);
+        assert_eq!(expired.get_or_insert_with("lamb", || "Lamball"), "Lamball");
+        assert_eq!(
+            expired.get_or_insert_with("lamb", || "Lambwool"),
+            "Lambwool"
+        );
+    }
+
+    #[test]
+    fn test_capacity() {
+        let cache = TtlCache::new(Duration::from_secs(60)).with_capacity(2);
+        cache.insert("lamball", 1);
+        cache.insert("cattiva", 2);
+        cache.insert("cattiva", 3);
+        assert_eq!(cache.get(&"lamball"), Some(1));
+
+        cache.insert("chikipi", 4);
+        assert_eq!(cache.get(&"lamball"), None);
+        assert_eq!(cache.get(&"cattiva"), Some(3));
+        assert_eq!(cache.get(&"chikipi"), Some(4));
+    }
+
+    #[test]
+    fn test_get_expired() {
+        let cache = TtlCache::new(Duration::ZERO);
+        cache.insert("lamball", 1);
+
+        assert_eq!(cache.get(&"lamball"), None);
+    }
+}
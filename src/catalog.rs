@@ -0,0 +1,656 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use inflector::Inflector;
+
+use crate::api::Pal;
+use crate::autocomplete::Completer;
+use crate::cache::TtlCache;
+use crate::config::Features;
+use crate::describe::DescriptionIndex;
+
+/// How long the results of queries like `/suitability` are reused for. A catalog refresh also
+/// discards them, since each catalog caches its own.
+const QUERY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How many results of each kind of query a catalog keeps at once.
+const QUERY_CACHE_CAPACITY: usize = 256;
+
+/// The names of Pals suited to a work type, with their levels, highest level first.
+pub type Suited = Vec<(String, i64)>;
+
+/// Every Pal known to the API, with an autocomplete engine over their names.
+pub struct Catalog {
+    pub pals: Vec<Pal>,
+    /// The Pals' names, sorted and without duplicates.
+    pub names: Vec<String>,
+    pub completer: Arc<dyn Completer>,
+    /// The names of the Pals dropping each item, keyed by the item in snake case.
+    pub drops: BTreeMap<String, Vec<String>>,
+    /// Every dropped item's name, in title case and sorted.
+    pub drop_names: Vec<String>,
+    pub drop_completer: Arc<dyn Completer>,
+    /// Every work type any Pal is suited to, in title case and sorted.
+    pub work_types: Vec<String>,
+    /// The names and levels of the Pals suited to each work type, keyed by the work type in snake
+    /// case, highest level first and then alphabetically.
+    pub suitability: BTreeMap<String, Vec<(String, i64)>>,
+    /// The index searched by `/describe`.
+    pub describe: DescriptionIndex,
+    /// When the Pals were fetched.
+    pub loaded_at: SystemTime,
+    /// The names of the Pals that weren't in the catalog this one replaced, sorted, or `None` if
+    /// it replaced none.
+    pub added: Option<Vec<String>>,
+    /// Recent [`Catalog::suited_to`] results, keyed by snake-case work type and minimum level.
+    pub suited_queries: TtlCache<(String, i64), Arc<Suited>>,
+    /// Recent `/describe` results, keyed by the normalized query.
+    pub describe_queries: TtlCache<String, Arc<Vec<String>>>,
+    /// The position in `pals` of the first Pal with each Paldeck number.
+    by_id: HashMap<i64, usize>,
+}
+
+impl Catalog {
+    /// Create a catalog of `pals`, with autocomplete and search configured by `features`.
+    pub fn new(pals: Vec<Pal>, features: Features) -> Self {
+        let backend = features.autocomplete_backend;
+        let mut names = pals.iter().map(|pal| pal.name.clone()).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        let mut drops: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for pal in &pals {
+            for item in &pal.drops {
+                drops
+                    .entry(item.to_snake_case())
+                    .or_default()
+                    .push(pal.name.clone());
+            }
+        }
+        for droppers in drops.values_mut() {
+            droppers.sort();
+            droppers.dedup();
+        }
+
+        let mut drop_names = drops
+            .keys()
+            .map(|item| item.to_title_case())
+            .collect::<Vec<_>>();
+        drop_names.sort();
+
+        let mut suitability: BTreeMap<String, Vec<(String, i64)>> = BTreeMap::new();
+        for pal in &pals {
+            for entry in &pal.suitability {
+                suitability
+                    .entry(entry.type_field.to_snake_case())
+                    .or_default()
+                    .push((pal.name.clone(), entry.level));
+            }
+        }
+        for suited in suitability.values_mut() {
+            suited.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            suited.dedup();
+        }
+
+        let work_types = suitability
+            .keys()
+            .map(|work_type| work_type.to_title_case())
+            .collect::<Vec<_>>();
+
+        let mut by_id = HashMap::new();
+        for (i, pal) in pals.iter().enumerate() {
+            by_id.entry(pal.id).or_insert(i);
+        }
+
+        Self {
+            completer: Arc::from(backend.build(
+                &names,
+                features.fuzzy_threshold,
+                features.prefix_weight,
+            )),
+            drop_completer: Arc::from(backend.build(
+                &drop_names,
+                features.fuzzy_threshold,
+                features.prefix_weight,
+            )),
+            describe: DescriptionIndex::new(&pals, features.describe_fields),
+            pals,
+            names,
+            drops,
+            drop_names,
+            work_types,
+            suitability,
+            loaded_at: SystemTime::now(),
+            added: None,
+            suited_queries: TtlCache::new(QUERY_CACHE_TTL).with_capacity(QUERY_CACHE_CAPACITY),
+            describe_queries: TtlCache::new(QUERY_CACHE_TTL).with_capacity(QUERY_CACHE_CAPACITY),
+            by_id,
+        }
+    }
+
+    /// Records which of this catalog's Pals aren't in `previous`, which it replaces.
+    pub fn replacing(self, previous: &Catalog) -> Self {
+        let added = self
+            .names
+            .iter()
+            .filter(|name| previous.names.binary_search(name).is_err())
+            .cloned()
+            .collect();
+        Self {
+            added: Some(added),
+            ..self
+        }
+    }
+
+    /// The Pal with Paldeck number `id`. Of variants sharing a number, the first the API listed
+    /// wins.
+    pub fn by_id(&self, id: i64) -> Option<&Pal> {
+        self.by_id.get(&id).map(|&i| &self.pals[i])
+    }
+
+    /// Whether a Pal named `name` is in the catalog, ignoring case.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(name))
+    }
+
+    /// The names and levels of the Pals suited to `work_type` at `min_level` or better, highest
+    /// level first. The work type is matched regardless of case or spacing.
+    pub fn suited_to(&self, work_type: &str, min_level: i64) -> Vec<(&str, i64)> {
+        self.suitability
+            .get(&work_type.to_snake_case())
+            .into_iter()
+            .flatten()
+            .take_while(|(_, level)| *level >= min_level)
+            .map(|(name, level)| (name.as_str(), *level))
+            .collect()
+    }
+
+    /// The names of the Pals that drop `item`, matched regardless of case or spacing.
+    pub fn droppers(&self, item: &str) -> &[String] {
+        self.drops
+            .get(&item.to_snake_case())
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Finds the Pal with the highest level for each work type, ordered by work type.
+///
+/// Ties go to the Pal with the lowest Paldeck number, then the alphabetically first name.
+pub fn specialists(pals: &[Pal]) -> Vec<(&str, &Pal, i64)> {
+    let mut best: BTreeMap<&str, (&Pal, i64)> = BTreeMap::new();
+
+    for pal in pals {
+        for suitability in &pal.suitability {
+            let candidate = (pal, suitability.level);
+            best.entry(&suitability.type_field)
+                .and_modify(|current| {
+                    if outranks(candidate, *current) {
+                        *current = candidate;
+                    }
+                })
+                .or_insert(candidate);
+        }
+    }
+
+    best.into_iter()
+        .map(|(work_type, (pal, level))| (work_type, pal, level))
+        .collect()
+}
+
+/// Parses weighted work types like `kindling:2,mining:1` into a map from snake-case work type to
+/// weight. A work type without a weight counts once.
+pub fn parse_weights(spec: &str) -> Result<BTreeMap<String, f64>, String> {
+    let mut weights = BTreeMap::new();
+
+    for entry in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let (work_type, weight) = match entry.split_once(':') {
+            Some((work_type, weight)) => {
+                let weight = weight
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|weight| weight.is_finite())
+                    .ok_or_else(|| format!("`{}` isn't a valid weight", weight.trim()))?;
+                (work_type.trim(), weight)
+            }
+            None => (entry, 1.0),
+        };
+
+        if work_type.is_empty() {
+            return Err(format!("`{entry}` is missing a work type"));
+        }
+        *weights.entry(work_type.to_snake_case()).or_default() += weight;
+    }
+
+    if weights.is_empty() {
+        return Err("No work types given".to_string());
+    }
+    Ok(weights)
+}
+
+/// The sum of `pal`'s work suitability levels, each multiplied by its work type's weight.
+pub fn weighted_suitability_score(pal: &Pal, weights: &BTreeMap<String, f64>) -> f64 {
+    pal.suitability
+        .iter()
+        .filter_map(|suitability| {
+            weights
+                .get(&suitability.type_field.to_snake_case())
+                .map(|weight| weight * suitability.level as f64)
+        })
+        .sum()
+}
+
+/// The `n` Pals with the highest positive weighted suitability scores, best first. Ties go to the
+/// Pal with the lowest Paldeck number.
+pub fn rank_by_weights<'a>(
+    pals: &'a [Pal],
+    weights: &BTreeMap<String, f64>,
+    n: usize,
+) -> Vec<(&'a Pal, f64)> {
+    let mut ranked = pals
+        .iter()
+        .map(|pal| (pal, weighted_suitability_score(pal, weights)))
+        .filter(|(_, score)| *score > 0.0)
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+    ranked.truncate(n);
+    ranked
+}
+
+/// How many different items `pals` drop, matched regardless of case or spacing, and how many
+/// drops they list altogether.
+pub fn distinct_drop_count(pals: &[Pal]) -> (usize, usize) {
+    let items = pals
+        .iter()
+        .flat_map(|pal| &pal.drops)
+        .map(|item| item.to_snake_case())
+        .collect::<HashSet<_>>();
+    let total = pals.iter().map(|pal| pal.drops.len()).sum();
+    (items.len(), total)
+}
+
+/// How unusual `pal`'s drops are: the average, over its distinct drops, of one over how many Pals
+/// in `droppers` drop each. A Pal whose drops no other Pal has scores 1, and one without drops 0.
+pub fn drop_uniqueness_score(pal: &Pal, droppers: &BTreeMap<String, Vec<String>>) -> f64 {
+    let mut items = pal
+        .drops
+        .iter()
+        .map(|item| item.to_snake_case())
+        .collect::<Vec<_>>();
+    items.sort();
+    items.dedup();
+    if items.is_empty() {
+        return 0.0;
+    }
+
+    let total = items
+        .iter()
+        .map(|item| 1.0 / droppers.get(item).map_or(1, Vec::len).max(1) as f64)
+        .sum::<f64>();
+    total / items.len() as f64
+}
+
+/// The `n` Pals with the most unusual drops by [`drop_uniqueness_score`], most unusual first.
+/// Pals without drops are left out, and ties go to the Pal with the lowest Paldeck number.
+pub fn rank_by_drop_uniqueness<'a>(
+    pals: &'a [Pal],
+    droppers: &BTreeMap<String, Vec<String>>,
+    n: usize,
+) -> Vec<(&'a Pal, f64)> {
+    let mut ranked = pals
+        .iter()
+        .map(|pal| (pal, drop_uniqueness_score(pal, droppers)))
+        .filter(|(_, score)| *score > 0.0)
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+    ranked.truncate(n);
+    ranked
+}
+
+/// Which way a list is sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Order {
+    #[name = "Shortest first"]
+    Ascending,
+    #[name = "Longest first"]
+    Descending,
+}
+
+/// The first `n` Pals sorted by the length of their descriptions in characters, with those
+/// lengths. Ties go to the alphabetically first name either way.
+pub fn by_description_length(pals: &[Pal], order: Order, n: usize) -> Vec<(&Pal, usize)> {
+    let mut sorted = pals
+        .iter()
+        .map(|pal| (pal, pal.description.chars().count()))
+        .collect::<Vec<_>>();
+    sorted.sort_by(|a, b| {
+        let by_length = match order {
+            Order::Ascending => a.1.cmp(&b.1),
+            Order::Descending => b.1.cmp(&a.1),
+        };
+        by_length.then_with(|| a.0.name.cmp(&b.0.name))
+    });
+    sorted.truncate(n);
+    sorted
+}
+
+/// Whether `a` should replace `b` as a work type's specialist.
+fn outranks(a: (&Pal, i64), b: (&Pal, i64)) -> bool {
+    (a.1, b.0.id, &b.0.name) > (b.1, a.0.id, &a.0.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Suitability;
+    use crate::autocomplete::Backend;
+
+    fn prefix() -> Features {
+        Features {
+            autocomplete_backend: Backend::Prefix,
+            ..Default::default()
+        }
+    }
+
+    fn pal(id: i64, name: &str, suitability: &[(&str, i64)]) -> Pal {
+        Pal {
+            id,
+            name: name.to_string(),
+            suitability: suitability
+                .iter()
+                .map(|(type_field, level)| Suitability {
+                    type_field: (*type_field).to_string(),
+                    level: *level,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_specialists() {
+        let pals = vec![
+            pal(5, "Foxparks", &[("kindling", 1)]),
+            pal(9, "Jormuntide Ignis", &[("kindling", 4)]),
+            pal(3, "Pengullet", &[("watering", 1), ("cooling", 1)]),
+            pal(2, "Lamball", &[("handiwork", 1)]),
+            pal(7, "Cattiva", &[("handiwork", 1), ("mining", 1)]),
+            pal(4, "Penking", &[("cooling", 2), ("watering", 2)]),
+        ];
+
+        let best = specialists(&pals)
+            .into_iter()
+            .map(|(work_type, pal, level)| (work_type, pal.name.as_str(), level))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            best,
+            vec![
+                ("cooling", "Penking", 2),
+                ("handiwork", "Lamball", 1),
+                ("kindling", "Jormuntide Ignis", 4),
+                ("mining", "Cattiva", 1),
+                ("watering", "Penking", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_by_id() {
+        let catalog = Catalog::new(
+            vec![
+                pal(101, "Jormuntide", &[]),
+                pal(1, "Lamball", &[]),
+                pal(101, "Jormuntide Ignis", &[]),
+            ],
+            prefix(),
+        );
+
+        assert_eq!(catalog.by_id(1).unwrap().name, "Lamball");
+        assert_eq!(catalog.by_id(101).unwrap().name, "Jormuntide");
+        assert!(catalog.by_id(2).is_none());
+    }
+
+    #[test]
+    fn test_by_description_length() {
+        let described = |name: &str, description: &str| Pal {
+            name: name.to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        };
+        let pals = vec![
+            described("Lamball", "Fluffy."),
+            described("Cattiva", "Likes to be petted by everyone."),
+            described("Chikipi", "Lays eggs."),
+            described("Foxparks", "Breathes fire!"),
+            described("Élan", "Fluffé."),
+        ];
+        let names = |order, n| {
+            by_description_length(&pals, order, n)
+                .into_iter()
+                .map(|(pal, length)| (pal.name.as_str(), length))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(Order::Descending, 3),
+            vec![("Cattiva", 31), ("Foxparks", 14), ("Chikipi", 10)]
+        );
+        assert_eq!(
+            names(Order::Ascending, 3),
+            vec![("Lamball", 7), ("Élan", 7), ("Chikipi", 10)]
+        );
+    }
+
+    #[test]
+    fn test_specialists_ties() {
+        let pals = vec![
+            pal(2, "Beta", &[("mining", 2)]),
+            pal(1, "Zeta", &[("mining", 2)]),
+            pal(1, "Alpha", &[("mining", 2)]),
+        ];
+
+        let best = specialists(&pals);
+        assert_eq!(best.len(), 1);
+        assert_eq!(best[0].1.name, "Alpha");
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        let weights = parse_weights("Kindling:2, mining:1.5,handiwork,mining:0.5").unwrap();
+        assert_eq!(
+            weights.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("handiwork".to_string(), 1.0),
+                ("kindling".to_string(), 2.0),
+                ("mining".to_string(), 2.0),
+            ]
+        );
+        assert_eq!(
+            parse_weights("Generating Electricity:3").unwrap()["generating_electricity"],
+            3.0
+        );
+
+        assert!(parse_weights("kindling:lots").is_err());
+        assert!(parse_weights(":2").is_err());
+        assert!(parse_weights(" , ").is_err());
+    }
+
+    #[test]
+    fn test_weighted_suitability_score() {
+        let pals = vec![
+            pal(1, "Foxparks", &[("kindling", 1)]),
+            pal(2, "Digtoise", &[("mining", 3)]),
+            pal(3, "Rooby", &[("kindling", 1), ("mining", 1)]),
+            pal(4, "Lamball", &[("farming", 1)]),
+            pal(5, "Arsox", &[("kindling", 2)]),
+        ];
+        let weights = parse_weights("kindling:2,mining:1").unwrap();
+
+        assert_eq!(weighted_suitability_score(&pals[2], &weights), 3.0);
+        assert_eq!(weighted_suitability_score(&pals[3], &weights), 0.0);
+
+        let ranked = rank_by_weights(&pals, &weights, 3)
+            .into_iter()
+            .map(|(pal, score)| (pal.name.as_str(), score))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![("Arsox", 4.0), ("Digtoise", 3.0), ("Rooby", 3.0)]
+        );
+    }
+
+    #[test]
+    fn test_replacing() {
+        let named = |names: &[&str]| {
+            let pals = names
+                .iter()
+                .map(|name| pal(0, name, &[]))
+                .collect::<Vec<_>>();
+            Catalog::new(pals, Features::default())
+        };
+        let old = named(&["Lamball", "Cattiva"]);
+        assert_eq!(old.added, None);
+
+        let new = named(&["Lamball", "Cattiva", "Chikipi", "Anubis"]).replacing(&old);
+        assert_eq!(
+            new.added,
+            Some(vec!["Anubis".to_string(), "Chikipi".to_string()])
+        );
+        assert_eq!(named(&["Lamball"]).replacing(&old).added, Some(vec![]));
+    }
+
+    #[test]
+    fn test_distinct_drop_count() {
+        let mut lamball = pal(1, "Lamball", &[]);
+        lamball.drops = vec!["wool".to_string(), "lamball_mutton".to_string()];
+        let mut cremis = pal(2, "Cremis", &[]);
+        cremis.drops = vec!["Wool".to_string()];
+        let pals = vec![lamball, cremis, pal(3, "Tanzee", &[])];
+
+        assert_eq!(distinct_drop_count(&pals), (2, 3));
+        assert_eq!(distinct_drop_count(&[]), (0, 0));
+    }
+
+    #[test]
+    fn test_drop_uniqueness_score() {
+        let with_drops = |id, name: &str, drops: &[&str]| Pal {
+            id,
+            name: name.to_string(),
+            drops: drops.iter().map(|drop| drop.to_string()).collect(),
+            ..Default::default()
+        };
+        let pals = vec![
+            with_drops(1, "Lamball", &["wool", "lamball_mutton"]),
+            with_drops(2, "Cattiva", &["red_berries"]),
+            with_drops(3, "Cremis", &["wool", "wool"]),
+            with_drops(4, "Wooly", &["wool"]),
+            with_drops(5, "Tanzee", &[]),
+        ];
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let droppers = BTreeMap::from([
+            (
+                "wool".to_string(),
+                names(&["Lamball", "Cremis", "Wooly", "Sibelyx"]),
+            ),
+            ("lamball_mutton".to_string(), names(&["Lamball"])),
+        ]);
+
+        assert_eq!(drop_uniqueness_score(&pals[0], &droppers), 0.625);
+        assert_eq!(drop_uniqueness_score(&pals[1], &droppers), 1.0);
+        assert_eq!(drop_uniqueness_score(&pals[2], &droppers), 0.25);
+        assert_eq!(drop_uniqueness_score(&pals[4], &droppers), 0.0);
+
+        let ranked = rank_by_drop_uniqueness(&pals, &droppers, 3)
+            .into_iter()
+            .map(|(pal, score)| (pal.name.as_str(), score))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![("Cattiva", 1.0), ("Lamball", 0.625), ("Cremis", 0.25)]
+        );
+    }
+
+    #[test]
+    fn test_catalog() {
+        let catalog = Catalog::new(
+            vec![pal(2, "Lamball", &[]), pal(1, "Cattiva", &[])],
+            prefix(),
+        );
+
+        assert_eq!(catalog.names, vec!["Cattiva", "Lamball"]);
+        assert!(catalog.contains("lamball"));
+        assert!(!catalog.contains("Chikipi"));
+        assert_eq!(catalog.completer.search("lam", 10), vec!["Lamball"]);
+    }
+
+    #[test]
+    fn test_work_types() {
+        let catalog = Catalog::new(
+            vec![
+                pal(1, "Foxparks", &[("kindling", 1)]),
+                pal(2, "Pengullet", &[("watering", 1), ("Kindling", 1)]),
+                pal(3, "Lamball", &[("generating_electricity", 1)]),
+            ],
+            prefix(),
+        );
+
+        assert_eq!(
+            catalog.work_types,
+            vec!["Generating Electricity", "Kindling", "Watering"]
+        );
+    }
+
+    #[test]
+    fn test_suited_to() {
+        let catalog = Catalog::new(
+            vec![
+                pal(5, "Foxparks", &[("kindling", 1)]),
+                pal(9, "Jormuntide Ignis", &[("kindling", 4)]),
+                pal(8, "Arsox", &[("Kindling", 2)]),
+                pal(2, "Rooby", &[("kindling", 2)]),
+                pal(3, "Pengullet", &[("watering", 1)]),
+                pal(1, "Flambelle", &[("kindling", 0)]),
+            ],
+            prefix(),
+        );
+
+        assert_eq!(
+            catalog.suited_to("KINDLING", 1),
+            vec![
+                ("Jormuntide Ignis", 4),
+                ("Arsox", 2),
+                ("Rooby", 2),
+                ("Foxparks", 1)
+            ]
+        );
+        assert_eq!(
+            catalog.suited_to("kindling", 3),
+            vec![("Jormuntide Ignis", 4)]
+        );
+        assert!(catalog.suited_to("kindling", 5).is_empty());
+        assert!(catalog.suited_to("mining", 1).is_empty());
+    }
+
+    #[test]
+    fn test_droppers() {
+        let mut lamball = pal(1, "Lamball", &[]);
+        lamball.drops = vec!["wool".to_string(), "lamball_mutton".to_string()];
+        let mut cremis = pal(2, "Cremis", &[]);
+        cremis.drops = vec!["wool".to_string()];
+        let catalog = Catalog::new(vec![lamball, cremis], prefix());
+
+        assert_eq!(catalog.droppers("Wool"), ["Cremis", "Lamball"]);
+        assert_eq!(catalog.droppers("Lamball Mutton"), ["Lamball"]);
+        assert!(catalog.droppers("Flame Organ").is_empty());
+        assert_eq!(catalog.drop_names, vec!["Lamball Mutton", "Wool"]);
+        assert_eq!(catalog.drop_completer.search("wo", 10), vec!["Wool"]);
+    }
+}
@@ -0,0 +1,878 @@
+use std::env;
+use std::fmt::Display;
+#[cfg(feature = "web")]
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::Url;
+
+use crate::api::normalize_base_url;
+use crate::autocomplete::{Backend, DEFAULT_PREFIX_WEIGHT, DEFAULT_THRESHOLD};
+use crate::describe::SearchFields;
+use crate::guilds::{parse_guild_ids, GuildAccess};
+use crate::headers::{bearer, parse_headers};
+use crate::labels::{DEFAULT_LANGUAGE, LANGUAGES};
+use crate::pagination::{clamp_page_size, MAX_PAGE_SIZE};
+
+/// How long to wait for a response from the Palworld API, unless `PAL_API_TIMEOUT_SECS` is set.
+const DEFAULT_API_TIMEOUT_SECS: u64 = 10;
+
+/// How many times a failed request is retried, unless `PAL_API_RETRIES` is set.
+const DEFAULT_API_RETRIES: u32 = 2;
+
+/// The most times `PAL_API_RETRIES` may retry a failed request, so a slash command can't wait on
+/// retries for minutes.
+const MAX_API_RETRIES: u32 = 10;
+
+/// How long a fetched Pal is cached, unless `PAL_CACHE_TTL_SECS` is set.
+const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
+
+/// How often the catalog is refetched, unless `PAL_REFRESH_SECS` or `PAL_NAMES_REFRESH_SECS` is
+/// set.
+const DEFAULT_NAMES_REFRESH_SECS: u64 = 6 * 60 * 60;
+
+/// What prefix commands start with, unless `COMMAND_PREFIX` is set.
+const DEFAULT_PREFIX: &str = "!";
+
+/// How long an idle pooled connection is kept open, unless `PAL_API_POOL_IDLE_TIMEOUT_SECS` is
+/// set.
+const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+/// The most idle pooled connections kept per host, unless `PAL_API_POOL_MAX_IDLE_PER_HOST` is set.
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// API calls slower than this are logged, unless `PAL_API_SLOW_MS` is set.
+const DEFAULT_API_SLOW_MS: u64 = 2000;
+
+/// Everything the bot reads from the environment at startup.
+#[derive(Debug)]
+pub struct Config {
+    /// The bot's token (`DISCORD_TOKEN`).
+    pub discord_token: String,
+    /// The base URL of the Paldex API (the first in `PAL_API_URL`).
+    pub pal_api_url: Url,
+    /// Mirrors of the API tried in order when it fails (the rest of `PAL_API_URL`).
+    pub fallback_api_urls: Vec<Url>,
+    /// How long to wait for the API (`PAL_API_TIMEOUT_SECS`).
+    pub api_timeout: Duration,
+    /// How many times a failed request is retried (`PAL_API_RETRIES`).
+    pub api_retries: u32,
+    /// API calls slower than this are logged (`PAL_API_SLOW_MS`).
+    pub slow_threshold: Duration,
+    /// How the API client pools connections (`PAL_API_POOL_IDLE_TIMEOUT_SECS` and
+    /// `PAL_API_POOL_MAX_IDLE_PER_HOST`).
+    pub api_pool: PoolSettings,
+    /// Extra headers sent with every API request (`PAL_API_HEADERS`), including the bearer token
+    /// (`PAL_API_TOKEN`).
+    pub api_headers: HeaderMap,
+    /// Where a fresh bearer token is fetched when the API answers 401, if anywhere
+    /// (`PAL_API_AUTH_URL`).
+    pub api_auth_url: Option<Url>,
+    /// What prefix commands start with (`COMMAND_PREFIX`).
+    pub command_prefix: String,
+    /// How long a fetched Pal is cached (`PAL_CACHE_TTL_SECS`).
+    pub cache_ttl: Duration,
+    /// How often the catalog is refetched (`PAL_REFRESH_SECS`).
+    pub refresh_interval: Duration,
+    /// Where lookup counts are persisted, if anywhere (`POPULAR_STATS_PATH`).
+    pub popular_stats_path: Option<PathBuf>,
+    /// The JSON file of Pal aliases, if any (`PAL_ALIASES_PATH`).
+    pub aliases_path: Option<PathBuf>,
+    /// The language Pal data is fetched in for guilds that haven't chosen one (`PAL_LANGUAGE`).
+    pub default_language: String,
+    /// Where each guild's chosen language is persisted, if anywhere (`GUILD_LANGUAGES_PATH`).
+    pub guild_languages_path: Option<PathBuf>,
+    /// Which guilds are served (`ALLOWED_GUILD_IDS`, `DENIED_GUILD_IDS` and
+    /// `LEAVE_DENIED_GUILDS`).
+    pub guild_access: GuildAccess,
+    /// The author line shown on Pal embeds, if any (`EMBED_AUTHOR_NAME` and `EMBED_AUTHOR_ICON`).
+    pub embed_author: Option<EmbedAuthor>,
+    /// Lowercased hosts whose images are left out of embeds (`IMAGE_HOST_DENYLIST`).
+    pub image_host_denylist: Vec<String>,
+    /// Where the web view of Pals is served, if anywhere (`WEB_ADDR`).
+    #[cfg(feature = "web")]
+    pub web_addr: Option<SocketAddr>,
+    pub features: Features,
+}
+
+impl Config {
+    /// Reads the configuration from the process environment.
+    pub fn from_env() -> Result<Self> {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    /// Reads the configuration using `lookup` to fetch each variable.
+    ///
+    /// Missing or invalid required variables are errors naming the variable, all reported
+    /// together so they can be fixed in one go. Invalid optional ones are ignored with a
+    /// warning.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut errors = Vec::new();
+
+        let discord_token = lookup("DISCORD_TOKEN").filter(|token| !token.trim().is_empty());
+        if discord_token.is_none() {
+            errors.push("DISCORD_TOKEN is not set; set it to the bot's token".to_string());
+        }
+
+        let mut api_urls = Vec::new();
+        let raw_urls = lookup("PAL_API_URL").unwrap_or_default();
+        let raw_urls = raw_urls
+            .split(',')
+            .map(str::trim)
+            .filter(|raw| !raw.is_empty())
+            .collect::<Vec<_>>();
+        for raw in &raw_urls {
+            match Url::parse(raw) {
+                Err(err) => errors.push(format!("PAL_API_URL `{raw}` is not a valid URL: {err}")),
+                Ok(url) if !matches!(url.scheme(), "http" | "https") => {
+                    errors.push(format!("PAL_API_URL `{raw}` must be an http or https URL"))
+                }
+                Ok(url) => api_urls.push(normalize_base_url(url)),
+            }
+        }
+        if raw_urls.is_empty() {
+            errors.push("PAL_API_URL is not set; set it to the Paldex API's base URL".to_string());
+        }
+        let mut api_urls = api_urls.into_iter();
+        let pal_api_url = api_urls.next();
+        let fallback_api_urls = api_urls.collect();
+
+        let api_auth_url = lookup("PAL_API_AUTH_URL").and_then(|raw| {
+            Url::parse(&raw)
+                .map_err(|err| {
+                    errors.push(format!(
+                        "PAL_API_AUTH_URL `{raw}` is not a valid URL: {err}"
+                    ))
+                })
+                .ok()
+        });
+
+        let mut api_headers =
+            lookup("PAL_API_HEADERS").map_or_else(HeaderMap::new, |raw| parse_headers(&raw));
+        if let Some(token) = lookup("PAL_API_TOKEN").filter(|token| !token.trim().is_empty()) {
+            match bearer(&token) {
+                Some(value) => {
+                    api_headers.insert(AUTHORIZATION, value);
+                }
+                None => errors.push(
+                    "PAL_API_TOKEN contains characters that can't be sent in a header".to_string(),
+                ),
+            }
+        }
+
+        let api_timeout_secs = parsed(&lookup, "PAL_API_TIMEOUT_SECS", DEFAULT_API_TIMEOUT_SECS);
+        if api_timeout_secs == 0 {
+            errors.push(
+                "PAL_API_TIMEOUT_SECS is 0, which fails every request; set it to at least 1"
+                    .to_string(),
+            );
+        }
+
+        let default_language = lookup("PAL_LANGUAGE").map_or_else(
+            || DEFAULT_LANGUAGE.to_string(),
+            |raw| raw.trim().to_lowercase(),
+        );
+        if !LANGUAGES.contains(&default_language.as_str()) {
+            errors.push(format!(
+                "PAL_LANGUAGE `{default_language}` isn't supported; try one of {}",
+                LANGUAGES.join(", ")
+            ));
+        }
+
+        let command_prefix = lookup("COMMAND_PREFIX").unwrap_or_else(|| DEFAULT_PREFIX.to_string());
+        if command_prefix.trim().is_empty() {
+            errors.push("COMMAND_PREFIX is blank; unset it to use the default".to_string());
+        }
+
+        #[cfg(feature = "web")]
+        let web_addr = lookup("WEB_ADDR").and_then(|raw| {
+            raw.trim()
+                .parse::<SocketAddr>()
+                .map_err(|err| {
+                    errors.push(format!("WEB_ADDR `{raw}` is not a valid address: {err}"))
+                })
+                .ok()
+        });
+
+        let mut guild_ids = |key: &str| {
+            lookup(key).and_then(|raw| {
+                parse_guild_ids(&raw)
+                    .map_err(|err| errors.push(format!("{key}: {err}")))
+                    .ok()
+            })
+        };
+        let guild_access = GuildAccess {
+            allowed: guild_ids("ALLOWED_GUILD_IDS"),
+            denied: guild_ids("DENIED_GUILD_IDS").unwrap_or_default(),
+            leave: flag(&lookup, "LEAVE_DENIED_GUILDS", false),
+        };
+
+        let (Some(discord_token), Some(pal_api_url), true) =
+            (discord_token, pal_api_url, errors.is_empty())
+        else {
+            return Err(anyhow!(errors.join("\n")));
+        };
+
+        let refresh_secs = parsed(
+            &lookup,
+            "PAL_REFRESH_SECS",
+            parsed(
+                &lookup,
+                "PAL_NAMES_REFRESH_SECS",
+                DEFAULT_NAMES_REFRESH_SECS,
+            ),
+        );
+
+        Ok(Self {
+            discord_token,
+            pal_api_url,
+            fallback_api_urls,
+            api_timeout: Duration::from_secs(api_timeout_secs),
+            api_retries: parsed(&lookup, "PAL_API_RETRIES", DEFAULT_API_RETRIES)
+                .min(MAX_API_RETRIES),
+            slow_threshold: Duration::from_millis(parsed(
+                &lookup,
+                "PAL_API_SLOW_MS",
+                DEFAULT_API_SLOW_MS,
+            )),
+            api_pool: PoolSettings {
+                idle_timeout: Duration::from_secs(parsed(
+                    &lookup,
+                    "PAL_API_POOL_IDLE_TIMEOUT_SECS",
+                    DEFAULT_POOL_IDLE_TIMEOUT_SECS,
+                )),
+                max_idle_per_host: parsed(
+                    &lookup,
+                    "PAL_API_POOL_MAX_IDLE_PER_HOST",
+                    DEFAULT_POOL_MAX_IDLE_PER_HOST,
+                ),
+            },
+            api_headers,
+            api_auth_url,
+            command_prefix,
+            cache_ttl: Duration::from_secs(parsed(
+                &lookup,
+                "PAL_CACHE_TTL_SECS",
+                DEFAULT_CACHE_TTL_SECS,
+            )),
+            refresh_interval: Duration::from_secs(refresh_secs.max(1)),
+            popular_stats_path: lookup("POPULAR_STATS_PATH").map(PathBuf::from),
+            aliases_path: lookup("PAL_ALIASES_PATH").map(PathBuf::from),
+            default_language,
+            guild_languages_path: lookup("GUILD_LANGUAGES_PATH").map(PathBuf::from),
+            guild_access,
+            embed_author: embed_author(&lookup),
+            image_host_denylist: lookup("IMAGE_HOST_DENYLIST")
+                .map(|raw| {
+                    raw.split(',')
+                        .map(|host| host.trim().to_lowercase())
+                        .filter(|host| !host.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            #[cfg(feature = "web")]
+            web_addr,
+            features: Features::from_lookup(&lookup),
+        })
+    }
+}
+
+/// How the API client keeps idle connections around for reuse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    pub idle_timeout: Duration,
+    pub max_idle_per_host: usize,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT_SECS),
+            max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+        }
+    }
+}
+
+/// The author line shown on Pal embeds, for branding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedAuthor {
+    pub name: String,
+    pub icon_url: Option<Url>,
+}
+
+/// Reads the embed author using `lookup`. There is none unless `EMBED_AUTHOR_NAME` is set, and an
+/// icon that isn't an http or https URL is left out with a warning.
+fn embed_author(lookup: impl Fn(&str) -> Option<String>) -> Option<EmbedAuthor> {
+    let name = lookup("EMBED_AUTHOR_NAME").filter(|name| !name.trim().is_empty())?;
+    let icon_url = lookup("EMBED_AUTHOR_ICON").and_then(|raw| match Url::parse(raw.trim()) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Some(url),
+        Ok(_) => {
+            warn!("Ignoring EMBED_AUTHOR_ICON `{raw}`: it must be an http or https URL");
+            None
+        }
+        Err(err) => {
+            warn!("Ignoring EMBED_AUTHOR_ICON `{raw}`: {err}");
+            None
+        }
+    });
+
+    Some(EmbedAuthor {
+        name: name.trim().to_string(),
+        icon_url,
+    })
+}
+
+/// Feature toggles read from the environment once at startup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Features {
+    /// Reply to `/pal` with plain text instead of an embed by default (`PAL_PLAIN_TEXT`).
+    pub plain_text: bool,
+    /// The autocomplete engine to use (`AUTOCOMPLETE_BACKEND`).
+    pub autocomplete_backend: Backend,
+    /// How similar, from 0 to 1, a name must be to be suggested by fuzzy autocomplete
+    /// (`FUZZY_THRESHOLD`).
+    pub fuzzy_threshold: f64,
+    /// How much, from 0 up, starting with the query counts towards a fuzzy autocomplete
+    /// suggestion's rank (`PREFIX_WEIGHT`).
+    pub prefix_weight: f64,
+    /// The Pal fields searched by `/describe` (`DESCRIBE_FIELDS`).
+    pub describe_fields: SearchFields,
+    /// How many Pals `/list` shows on each page, from 1 to 25 (`LIST_PAGE_SIZE`).
+    pub list_page_size: usize,
+    /// How many candidates `/resolve` lists before asking for a narrower query
+    /// (`DISAMBIGUATION_LIMIT`).
+    pub disambiguation_limit: usize,
+    /// How many names are suggested when no Pal has the name asked for, from 1 to 5
+    /// (`SUGGESTION_COUNT`).
+    pub suggestion_count: usize,
+    /// How many characters `/inline` summaries may run to, from 1 to 2000 (`INLINE_MAX_CHARS`).
+    pub inline_max_chars: usize,
+    /// How many API-backed commands each user may run per `rate_limit_window`, or 0 for no limit
+    /// (`RATE_LIMIT_COMMANDS`).
+    pub rate_limit: u32,
+    /// The window the rate limit applies over (`RATE_LIMIT_WINDOW_SECS`).
+    pub rate_limit_window: Duration,
+}
+
+/// How many candidates `/resolve` lists, unless `DISAMBIGUATION_LIMIT` is set.
+const DEFAULT_DISAMBIGUATION_LIMIT: usize = 5;
+
+/// How many names are suggested for an unknown Pal, unless `SUGGESTION_COUNT` is set.
+const DEFAULT_SUGGESTION_COUNT: usize = 3;
+
+/// How many characters `/inline` summaries may run to, unless `INLINE_MAX_CHARS` is set.
+const DEFAULT_INLINE_MAX_CHARS: usize = 200;
+
+/// The most characters `INLINE_MAX_CHARS` may allow, which is all a Discord message holds.
+const MAX_INLINE_MAX_CHARS: usize = 2000;
+
+/// How many API-backed commands a user may run per window, unless `RATE_LIMIT_COMMANDS` is set.
+const DEFAULT_RATE_LIMIT: u32 = 5;
+
+/// The rate limit's window, unless `RATE_LIMIT_WINDOW_SECS` is set.
+const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 10;
+
+/// The most names `SUGGESTION_COUNT` may ask to suggest.
+const MAX_SUGGESTION_COUNT: usize = 5;
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            plain_text: false,
+            autocomplete_backend: Backend::default(),
+            fuzzy_threshold: DEFAULT_THRESHOLD,
+            prefix_weight: DEFAULT_PREFIX_WEIGHT,
+            describe_fields: SearchFields::default(),
+            list_page_size: MAX_PAGE_SIZE,
+            disambiguation_limit: DEFAULT_DISAMBIGUATION_LIMIT,
+            suggestion_count: DEFAULT_SUGGESTION_COUNT,
+            inline_max_chars: DEFAULT_INLINE_MAX_CHARS,
+            rate_limit: DEFAULT_RATE_LIMIT,
+            rate_limit_window: Duration::from_secs(DEFAULT_RATE_LIMIT_WINDOW_SECS),
+        }
+    }
+}
+
+impl Features {
+    /// Reads the toggles using `lookup` to fetch each variable, falling back to the default for
+    /// absent or invalid values.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            plain_text: flag(&lookup, "PAL_PLAIN_TEXT", defaults.plain_text),
+            autocomplete_backend: parsed(
+                &lookup,
+                "AUTOCOMPLETE_BACKEND",
+                defaults.autocomplete_backend,
+            ),
+            fuzzy_threshold: finite(&lookup, "FUZZY_THRESHOLD", defaults.fuzzy_threshold)
+                .clamp(0.0, 1.0),
+            prefix_weight: finite(&lookup, "PREFIX_WEIGHT", defaults.prefix_weight).max(0.0),
+            describe_fields: parsed(&lookup, "DESCRIBE_FIELDS", defaults.describe_fields),
+            list_page_size: clamp_page_size(parsed(
+                &lookup,
+                "LIST_PAGE_SIZE",
+                defaults.list_page_size,
+            )),
+            disambiguation_limit: parsed(
+                &lookup,
+                "DISAMBIGUATION_LIMIT",
+                defaults.disambiguation_limit,
+            )
+            .max(1),
+            suggestion_count: parsed(&lookup, "SUGGESTION_COUNT", defaults.suggestion_count)
+                .clamp(1, MAX_SUGGESTION_COUNT),
+            inline_max_chars: parsed(&lookup, "INLINE_MAX_CHARS", defaults.inline_max_chars)
+                .clamp(1, MAX_INLINE_MAX_CHARS),
+            rate_limit: parsed(&lookup, "RATE_LIMIT_COMMANDS", defaults.rate_limit),
+            rate_limit_window: Duration::from_secs(parsed(
+                &lookup,
+                "RATE_LIMIT_WINDOW_SECS",
+                DEFAULT_RATE_LIMIT_WINDOW_SECS,
+            )),
+        }
+    }
+}
+
+/// Parses a variable with `FromStr`, warning about and ignoring invalid values.
+fn parsed<T: FromStr>(lookup: impl Fn(&str) -> Option<String>, key: &str, default: T) -> T
+where
+    T::Err: Display,
+{
+    let Some(value) = lookup(key) else {
+        return default;
+    };
+
+    value.parse().unwrap_or_else(|err| {
+        warn!("Ignoring {key}: {err}");
+        default
+    })
+}
+
+/// Parses a number like `parsed`, also ignoring NaN and infinities, which no limit can clamp.
+fn finite(lookup: impl Fn(&str) -> Option<String>, key: &str, default: f64) -> f64 {
+    let value = parsed(&lookup, key, default);
+    if value.is_finite() {
+        value
+    } else {
+        warn!("Ignoring {key}: {value} is not a finite number");
+        default
+    }
+}
+
+/// Parses a boolean toggle, accepting the usual spellings of on and off.
+fn flag(lookup: impl Fn(&str) -> Option<String>, key: &str, default: bool) -> bool {
+    let Some(value) = lookup(key) else {
+        return default;
+    };
+
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => true,
+        "0" | "false" | "no" | "off" => false,
+        _ => {
+            warn!("Ignoring invalid value `{value}` for {key}");
+            default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    fn features(vars: &[(&str, &str)]) -> Features {
+        let vars = self::vars(vars);
+        Features::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    fn config(vars: &[(&str, &str)]) -> Result<Config> {
+        let vars = self::vars(vars);
+        Config::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn test_config() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("PAL_API_RETRIES", "5"),
+            ("PAL_API_TIMEOUT_SECS", "soon"),
+            ("PAL_NAMES_REFRESH_SECS", "60"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.discord_token, "token");
+        assert_eq!(config.pal_api_url.as_str(), "http://localhost:3000/");
+        assert_eq!(config.api_retries, 5);
+        assert_eq!(
+            config.api_timeout,
+            Duration::from_secs(DEFAULT_API_TIMEOUT_SECS)
+        );
+        assert_eq!(config.refresh_interval, Duration::from_secs(60));
+        assert_eq!(config.popular_stats_path, None);
+        assert_eq!(config.api_auth_url, None);
+        assert_eq!(config.command_prefix, "!");
+        assert_eq!(config.default_language, "en");
+        assert!(config.api_headers.is_empty());
+        assert_eq!(config.guild_access, GuildAccess::default());
+        assert_eq!(config.embed_author, None);
+        assert!(config.image_host_denylist.is_empty());
+        assert!(config.fallback_api_urls.is_empty());
+        assert_eq!(config.api_pool, PoolSettings::default());
+    }
+
+    #[test]
+    fn test_config_clamps_api_retries() {
+        for (raw, expected) in [
+            ("0", 0),
+            ("10", MAX_API_RETRIES),
+            ("4000000000", MAX_API_RETRIES),
+        ] {
+            let config = config(&[
+                ("DISCORD_TOKEN", "token"),
+                ("PAL_API_URL", "http://localhost:3000/"),
+                ("PAL_API_RETRIES", raw),
+            ])
+            .unwrap();
+            assert_eq!(config.api_retries, expected, "{raw}");
+        }
+    }
+
+    #[test]
+    fn test_config_api_pool() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("PAL_API_POOL_IDLE_TIMEOUT_SECS", "15"),
+            ("PAL_API_POOL_MAX_IDLE_PER_HOST", "32"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.api_pool,
+            PoolSettings {
+                idle_timeout: Duration::from_secs(15),
+                max_idle_per_host: 32,
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_image_host_denylist() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("IMAGE_HOST_DENYLIST", " Static.Example.net, ,blocked.org"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.image_host_denylist,
+            vec!["static.example.net", "blocked.org"]
+        );
+    }
+
+    #[test]
+    fn test_config_normalizes_api_urls() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            (
+                "PAL_API_URL",
+                "http://primary.test/api/v1//?, http://mirror.test/?key=abc",
+            ),
+        ])
+        .unwrap();
+        assert_eq!(config.pal_api_url.as_str(), "http://primary.test/api/v1");
+        assert_eq!(
+            config.fallback_api_urls[0].as_str(),
+            "http://mirror.test/?key=abc"
+        );
+    }
+
+    #[test]
+    fn test_config_fallback_api_urls() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            (
+                "PAL_API_URL",
+                "http://primary.test/, http://mirror.test/,,http://backup.test/",
+            ),
+        ])
+        .unwrap();
+        assert_eq!(config.pal_api_url.as_str(), "http://primary.test/");
+        assert_eq!(
+            config
+                .fallback_api_urls
+                .iter()
+                .map(Url::as_str)
+                .collect::<Vec<_>>(),
+            vec!["http://mirror.test/", "http://backup.test/"]
+        );
+
+        let err = self::config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://primary.test/,mirror"),
+        ])
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("PAL_API_URL `mirror` is not a valid URL"));
+
+        for blank in [",", " , "] {
+            let err =
+                self::config(&[("DISCORD_TOKEN", "token"), ("PAL_API_URL", blank)]).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "PAL_API_URL is not set; set it to the Paldex API's base URL"
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_default_language() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("PAL_LANGUAGE", " DE "),
+        ])
+        .unwrap();
+        assert_eq!(config.default_language, "de");
+    }
+
+    #[test]
+    fn test_config_guild_access() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("ALLOWED_GUILD_IDS", "1,2"),
+            ("DENIED_GUILD_IDS", "2"),
+            ("LEAVE_DENIED_GUILDS", "yes"),
+        ])
+        .unwrap();
+
+        assert!(config.guild_access.permits(Some(1)));
+        assert!(!config.guild_access.permits(Some(2)));
+        assert!(config.guild_access.leave);
+
+        let err = self::config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("DENIED_GUILD_IDS", "2,two"),
+        ])
+        .unwrap_err();
+        assert_eq!(err.to_string(), "DENIED_GUILD_IDS: `two` isn't a guild ID");
+    }
+
+    #[test]
+    fn test_config_api_token() {
+        let config = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("PAL_API_HEADERS", "X-Tenant:acme"),
+            ("PAL_API_TOKEN", "hunter2"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.api_headers.len(), 2);
+        assert_eq!(config.api_headers[AUTHORIZATION], "Bearer hunter2");
+    }
+
+    #[test]
+    fn test_config_aggregates_errors() {
+        let err = config(&[
+            ("PAL_API_URL", "ftp://localhost/"),
+            ("PAL_API_AUTH_URL", "nowhere"),
+            ("PAL_API_TIMEOUT_SECS", "0"),
+            ("PAL_LANGUAGE", "xx"),
+            ("COMMAND_PREFIX", " "),
+        ])
+        .unwrap_err()
+        .to_string();
+
+        let errors = err.lines().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 6, "{err}");
+        assert!(errors[0].starts_with("DISCORD_TOKEN is not set"));
+        assert!(errors[1].starts_with("PAL_API_URL `ftp://localhost/` must be"));
+        assert!(errors[2].starts_with("PAL_API_AUTH_URL `nowhere`"));
+        assert!(errors[3].starts_with("PAL_API_TIMEOUT_SECS is 0"));
+        assert!(errors[4].starts_with("PAL_LANGUAGE `xx` isn't supported"));
+        assert!(errors[5].starts_with("COMMAND_PREFIX is blank"));
+
+        assert!(config(&[])
+            .unwrap_err()
+            .to_string()
+            .contains("\nPAL_API_URL is not set"));
+    }
+
+    #[test]
+    fn test_config_missing_token() {
+        let err = config(&[("PAL_API_URL", "http://localhost:3000/")]).unwrap_err();
+        assert!(err.to_string().starts_with("DISCORD_TOKEN is not set"));
+
+        let err = config(&[
+            ("DISCORD_TOKEN", " "),
+            ("PAL_API_URL", "http://localhost:3000/"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().starts_with("DISCORD_TOKEN is not set"));
+    }
+
+    #[test]
+    fn test_config_malformed_url() {
+        let err = config(&[("DISCORD_TOKEN", "token"), ("PAL_API_URL", "not a url")]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("PAL_API_URL `not a url` is not a valid URL"));
+
+        let err = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "localhost:3000"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().ends_with("must be an http or https URL"));
+
+        let err = config(&[("DISCORD_TOKEN", "token")]).unwrap_err();
+        assert!(err.to_string().starts_with("PAL_API_URL is not set"));
+
+        let err = config(&[
+            ("DISCORD_TOKEN", "token"),
+            ("PAL_API_URL", "http://localhost:3000/"),
+            ("PAL_API_AUTH_URL", "/auth"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().starts_with("PAL_API_AUTH_URL `/auth`"));
+    }
+
+    #[test]
+    fn test_embed_author() {
+        let author = |vars: &[(&str, &str)]| {
+            let vars = self::vars(vars);
+            embed_author(|key| vars.get(key).cloned())
+        };
+
+        assert_eq!(author(&[]), None);
+        assert_eq!(
+            author(&[("EMBED_AUTHOR_ICON", "https://x.test/i.png")]),
+            None
+        );
+        assert_eq!(
+            author(&[
+                ("EMBED_AUTHOR_NAME", "Paldex"),
+                ("EMBED_AUTHOR_ICON", "https://x.test/i.png"),
+            ]),
+            Some(EmbedAuthor {
+                name: "Paldex".to_string(),
+                icon_url: Some(Url::parse("https://x.test/i.png").unwrap()),
+            })
+        );
+        for icon in ["not a url", "ftp://x.test/i.png"] {
+            assert_eq!(
+                author(&[("EMBED_AUTHOR_NAME", "Paldex"), ("EMBED_AUTHOR_ICON", icon)]),
+                Some(EmbedAuthor {
+                    name: "Paldex".to_string(),
+                    icon_url: None,
+                }),
+                "{icon}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_features_absent() {
+        assert_eq!(features(&[]), Features::default());
+    }
+
+    #[test]
+    fn test_features_present() {
+        let features = features(&[
+            ("PAL_PLAIN_TEXT", "Yes"),
+            ("AUTOCOMPLETE_BACKEND", "prefix"),
+            ("DESCRIBE_FIELDS", "aura"),
+            ("LIST_PAGE_SIZE", "10"),
+            ("DISAMBIGUATION_LIMIT", "8"),
+            ("SUGGESTION_COUNT", "4"),
+            ("INLINE_MAX_CHARS", "120"),
+            ("FUZZY_THRESHOLD", "0.6"),
+            ("PREFIX_WEIGHT", "0.25"),
+            ("RATE_LIMIT_COMMANDS", "0"),
+            ("RATE_LIMIT_WINDOW_SECS", "30"),
+        ]);
+
+        assert!(features.plain_text);
+        assert_eq!(features.autocomplete_backend, Backend::Prefix);
+        assert!(features.describe_fields.aura);
+        assert!(!features.describe_fields.name);
+        assert_eq!(features.list_page_size, 10);
+        assert_eq!(features.disambiguation_limit, 8);
+        assert_eq!(features.suggestion_count, 4);
+        assert_eq!(features.inline_max_chars, 120);
+        assert_eq!(features.fuzzy_threshold, 0.6);
+        assert_eq!(features.prefix_weight, 0.25);
+        assert_eq!(features.rate_limit, 0);
+        assert_eq!(features.rate_limit_window, Duration::from_secs(30));
+        assert_eq!(
+            self::features(&[("FUZZY_THRESHOLD", "2")]).fuzzy_threshold,
+            1.0
+        );
+        assert_eq!(
+            self::features(&[("FUZZY_THRESHOLD", "NaN")]).fuzzy_threshold,
+            DEFAULT_THRESHOLD
+        );
+        assert_eq!(
+            self::features(&[("PREFIX_WEIGHT", "-1")]).prefix_weight,
+            0.0
+        );
+        assert_eq!(
+            self::features(&[("PREFIX_WEIGHT", "inf")]).prefix_weight,
+            DEFAULT_PREFIX_WEIGHT
+        );
+        assert_eq!(
+            self::features(&[("SUGGESTION_COUNT", "9")]).suggestion_count,
+            5
+        );
+        assert_eq!(
+            self::features(&[("SUGGESTION_COUNT", "0")]).suggestion_count,
+            1
+        );
+        assert_eq!(
+            self::features(&[("INLINE_MAX_CHARS", "5000")]).inline_max_chars,
+            2000
+        );
+        assert_eq!(
+            self::features(&[("INLINE_MAX_CHARS", "0")]).inline_max_chars,
+            1
+        );
+        assert_eq!(
+            self::features(&[("LIST_PAGE_SIZE", "500")]).list_page_size,
+            25
+        );
+        assert_eq!(self::features(&[("LIST_PAGE_SIZE", "0")]).list_page_size, 1);
+        assert!(!self::features(&[("PAL_PLAIN_TEXT", "0")]).plain_text);
+    }
+
+    #[test]
+    fn test_features_invalid() {
+        let features = features(&[
+            ("PAL_PLAIN_TEXT", "maybe"),
+            ("AUTOCOMPLETE_BACKEND", "trie"),
+            ("DESCRIBE_FIELDS", "stats"),
+            ("LIST_PAGE_SIZE", "lots"),
+        ]);
+
+        assert_eq!(features, Features::default());
+    }
+}
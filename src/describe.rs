@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use crate::api::Pal;
+use crate::autocomplete::normalize;
+
+/// The text fields fed into the description search index, selected with `DESCRIBE_FIELDS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFields {
+    pub name: bool,
+    pub description: bool,
+    pub aura: bool,
+    pub drops: bool,
+}
+
+impl SearchFields {
+    /// No fields at all, which is never a useful index.
+    const NONE: Self = Self {
+        name: false,
+        description: false,
+        aura: false,
+        drops: false,
+    };
+}
+
+impl Default for SearchFields {
+    fn default() -> Self {
+        Self {
+            name: true,
+            description: true,
+            aura: false,
+            drops: false,
+        }
+    }
+}
+
+impl FromStr for SearchFields {
+    type Err = String;
+
+    /// Parses a comma-separated list of `name`, `description`, `aura` and `drops`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = Self::NONE;
+
+        for field in s
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+        {
+            match field.to_lowercase().as_str() {
+                "name" => fields.name = true,
+                "description" => fields.description = true,
+                "aura" => fields.aura = true,
+                "drops" => fields.drops = true,
+                other => return Err(format!("Unknown search field `{other}`")),
+            }
+        }
+
+        if fields == Self::NONE {
+            return Err("No search fields given".to_string());
+        }
+        Ok(fields)
+    }
+}
+
+/// A keyword index over the chosen text fields of every Pal.
+pub struct DescriptionIndex {
+    entries: Vec<(String, String)>,
+}
+
+impl DescriptionIndex {
+    /// Indexes `fields` of each of `pals`.
+    pub fn new(pals: &[Pal], fields: SearchFields) -> Self {
+        let entries = pals
+            .iter()
+            .map(|pal| {
+                let mut text = Vec::new();
+                if fields.name {
+                    text.push(pal.name.clone());
+                }
+                if fields.description {
+                    text.push(pal.description.clone());
+                }
+                if fields.aura {
+                    text.push(pal.aura.name.clone());
+                    text.push(pal.aura.description.clone());
+                }
+                if fields.drops {
+                    text.extend(pal.drops.iter().map(|drop| drop.replace('_', " ")));
+                }
+                (pal.name.clone(), normalize(&text.join(" ")))
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// The names of at most `limit` Pals whose indexed text contains every word of `query`.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<String> {
+        let words = normalize(query)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return vec![];
+        }
+
+        self.entries
+            .iter()
+            .filter(|(_, text)| words.iter().all(|word| text.contains(word.as_str())))
+            .map(|(name, _)| name.clone())
+            .take(limit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Aura;
+
+    fn pals() -> Vec<Pal> {
+        vec![
+            Pal {
+                name: "Lamball".to_string(),
+                description: "A fluffy Pal that rolls around".to_string(),
+                drops: vec!["lamball_mutton".to_string()],
+                aura: Aura {
+                    name: "Fluffy Shield".to_string(),
+                    description: "Deploys a shield when summoned".to_string(),
+                },
+                ..Default::default()
+            },
+            Pal {
+                name: "Foxparks".to_string(),
+                description: "It breathes fire when excited".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_search_default_fields() {
+        let index = DescriptionIndex::new(&pals(), SearchFields::default());
+
+        assert_eq!(index.search("FIRE", 10), vec!["Foxparks"]);
+        assert_eq!(index.search("fluffy rolls", 10), vec!["Lamball"]);
+        assert_eq!(index.search("lamb", 10), vec!["Lamball"]);
+        assert!(index.search("mutton", 10).is_empty());
+        assert!(index.search("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_aura() {
+        let with_aura = "name,description,aura".parse().unwrap();
+
+        assert_eq!(
+            DescriptionIndex::new(&pals(), with_aura).search("shield", 10),
+            vec!["Lamball"]
+        );
+        assert!(DescriptionIndex::new(&pals(), SearchFields::default())
+            .search("shield", 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_fields_from_str() {
+        assert_eq!(
+            " Drops , name".parse::<SearchFields>(),
+            Ok(SearchFields {
+                name: true,
+                description: false,
+                aura: false,
+                drops: true,
+            })
+        );
+        assert!("name,stats".parse::<SearchFields>().is_err());
+        assert!("".parse::<SearchFields>().is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+
+/// Which guilds the bot answers in, from `ALLOWED_GUILD_IDS` and `DENIED_GUILD_IDS`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuildAccess {
+    /// The only guilds served, if set.
+    pub allowed: Option<HashSet<u64>>,
+    /// Guilds never served, even if allowed.
+    pub denied: HashSet<u64>,
+    /// Leave a guild after refusing a command there (`LEAVE_DENIED_GUILDS`).
+    pub leave: bool,
+}
+
+impl GuildAccess {
+    /// Whether commands may be used in `guild`, or in direct messages if it is `None`.
+    ///
+    /// The denylist wins over the allowlist. Direct messages are only served when there is no
+    /// allowlist.
+    pub fn permits(&self, guild: Option<u64>) -> bool {
+        match guild {
+            Some(id) => {
+                let allowed = match &self.allowed {
+                    Some(allowed) => allowed.contains(&id),
+                    None => true,
+                };
+                allowed && !self.denied.contains(&id)
+            }
+            None => self.allowed.is_none(),
+        }
+    }
+}
+
+/// Parses a comma-separated list of guild IDs.
+pub fn parse_guild_ids(raw: &str) -> Result<HashSet<u64>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse::<u64>()
+                .map_err(|_| format!("`{id}` isn't a guild ID"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permits() {
+        let open = GuildAccess::default();
+        assert!(open.permits(Some(1)));
+        assert!(open.permits(None));
+
+        let access = GuildAccess {
+            allowed: Some(HashSet::from([1, 2])),
+            denied: HashSet::from([2, 3]),
+            leave: false,
+        };
+        assert!(access.permits(Some(1)));
+        assert!(!access.permits(Some(2)), "the denylist wins");
+        assert!(!access.permits(Some(3)));
+        assert!(!access.permits(Some(4)), "not allowed");
+        assert!(!access.permits(None));
+
+        let denied_only = GuildAccess {
+            denied: HashSet::from([3]),
+            ..Default::default()
+        };
+        assert!(!denied_only.permits(Some(3)));
+        assert!(denied_only.permits(Some(4)));
+        assert!(denied_only.permits(None));
+    }
+
+    #[test]
+    fn test_parse_guild_ids() {
+        assert_eq!(
+            parse_guild_ids(" 123, 456,,"),
+            Ok(HashSet::from([123, 456]))
+        );
+        assert!(parse_guild_ids("123,abc").is_err());
+    }
+}
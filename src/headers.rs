@@ -0,0 +1,121 @@
+use log::warn;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{Map, Value};
+
+/// Header names containing any of these are treated as secrets.
+const SECRET_MARKERS: [&str; 6] = ["auth", "key", "token", "secret", "password", "cookie"];
+
+/// Parses `PAL_API_HEADERS`, either a JSON object or a `key:value,key:value` list, into the
+/// headers sent with every API request.
+///
+/// Invalid entries are skipped with a warning naming the header but never its value.
+pub fn parse_headers(raw: &str) -> HeaderMap {
+    let raw = raw.trim();
+    let entries = if raw.starts_with('{') {
+        match serde_json::from_str::<Map<String, Value>>(raw) {
+            Ok(object) => object
+                .into_iter()
+                .filter_map(|(name, value)| match value {
+                    Value::String(value) => Some((name, value)),
+                    _ => {
+                        warn!(
+                            "Ignoring header `{name}` in PAL_API_HEADERS: the value isn't a string"
+                        );
+                        None
+                    }
+                })
+                .collect(),
+            Err(err) => {
+                warn!(
+                    "Ignoring PAL_API_HEADERS: invalid JSON at line {} column {}",
+                    err.line(),
+                    err.column()
+                );
+                vec![]
+            }
+        }
+    } else {
+        raw.split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .filter_map(|entry| match entry.split_once(':') {
+                Some((name, value)) => Some((name.trim().to_string(), value.trim().to_string())),
+                None => {
+                    warn!("Ignoring an entry in PAL_API_HEADERS without a `:`");
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in entries {
+        let Ok(header_name) = HeaderName::from_bytes(name.as_bytes()) else {
+            warn!("Ignoring invalid header name `{name}` in PAL_API_HEADERS");
+            continue;
+        };
+        let Ok(mut header_value) = HeaderValue::from_str(&value) else {
+            warn!("Ignoring header `{name}` in PAL_API_HEADERS: the value is invalid");
+            continue;
+        };
+
+        header_value.set_sensitive(looks_secret(header_name.as_str()));
+        headers.insert(header_name, header_value);
+    }
+
+    headers
+}
+
+/// An `Authorization` header value carrying `token` as a bearer token, or `None` if the token
+/// contains characters a header can't.
+pub fn bearer(token: &str) -> Option<HeaderValue> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", token.trim())).ok()?;
+    value.set_sensitive(true);
+    Some(value)
+}
+
+/// Whether the header called `name` probably carries a credential.
+fn looks_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_headers_list() {
+        let headers = parse_headers("X-Tenant: acme, X-Api-Key:hunter2,no-colon,bad name:x,");
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-tenant"], "acme");
+        assert_eq!(headers["x-api-key"], "hunter2");
+        assert!(headers["x-api-key"].is_sensitive());
+        assert!(!headers["x-tenant"].is_sensitive());
+    }
+
+    #[test]
+    fn test_parse_headers_json() {
+        let headers =
+            parse_headers(r#"{"X-Tenant": "acme", "X-Retries": 3, "X-Bad": "line\nbreak"}"#);
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers["x-tenant"], "acme");
+        assert!(parse_headers("{not json").is_empty());
+    }
+
+    #[test]
+    fn test_bearer() {
+        let value = bearer(" hunter2\n").unwrap();
+        assert_eq!(value, "Bearer hunter2");
+        assert!(value.is_sensitive());
+        assert_eq!(bearer("hunter\u{7}2"), None);
+    }
+
+    #[test]
+    fn test_looks_secret() {
+        assert!(looks_secret("Authorization"));
+        assert!(looks_secret("X-Session-Token"));
+        assert!(!looks_secret("X-Tenant"));
+    }
+}
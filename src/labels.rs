@@ -0,0 +1,81 @@
+/// The languages labels are translated into, as Discord locales without their regions.
+pub const LANGUAGES: &[&str] = &["en", "de", "es", "fr", "ja"];
+
+/// The language Pal data is fetched in, unless `PAL_LANGUAGE` is set or a guild chose its own.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// A label shown on Pal embeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelKey {
+    Number,
+    Type,
+    Types,
+    WorkSuitability,
+    WorkSuitabilities,
+    Drops,
+    Stats,
+}
+
+/// The label for `key` in `locale`, a Discord locale like `de` or `es-ES`. Locales without
+/// translations get English.
+pub fn label(locale: &str, key: LabelKey) -> &'static str {
+    use LabelKey::*;
+
+    let language = locale.split('-').next().unwrap_or_default();
+    match (language, key) {
+        ("de", Number) => "Nummer",
+        ("de", Type) => "Typ",
+        ("de", Types) => "Typen",
+        ("de", WorkSuitability) => "Arbeitseignung",
+        ("de", WorkSuitabilities) => "Arbeitseignungen",
+        ("de", Drops) => "Beute",
+        ("de", Stats) => "Werte",
+
+        ("es", Number) => "Número",
+        ("es", Type) => "Tipo",
+        ("es", Types) => "Tipos",
+        ("es", WorkSuitability) => "Aptitud laboral",
+        ("es", WorkSuitabilities) => "Aptitudes laborales",
+        ("es", Drops) => "Botín",
+        ("es", Stats) => "Estadísticas",
+
+        ("fr", Number) => "Numéro",
+        ("fr", Type) => "Type",
+        ("fr", Types) => "Types",
+        ("fr", WorkSuitability) => "Aptitude au travail",
+        ("fr", WorkSuitabilities) => "Aptitudes au travail",
+        ("fr", Drops) => "Butin",
+        ("fr", Stats) => "Statistiques",
+
+        ("ja", Number) => "番号",
+        ("ja", Type | Types) => "タイプ",
+        ("ja", WorkSuitability | WorkSuitabilities) => "作業適性",
+        ("ja", Drops) => "ドロップ",
+        ("ja", Stats) => "ステータス",
+
+        (_, Number) => "Number",
+        (_, Type) => "Type",
+        (_, Types) => "Types",
+        (_, WorkSuitability) => "Work Suitability",
+        (_, WorkSuitabilities) => "Work Suitabilities",
+        (_, Drops) => "Drops",
+        (_, Stats) => "Stats",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label("de", LabelKey::Drops), "Beute");
+        assert_eq!(label("es-ES", LabelKey::Types), "Tipos");
+        assert_eq!(label("en-US", LabelKey::Number), "Number");
+        assert_eq!(
+            label("xx", LabelKey::WorkSuitabilities),
+            "Work Suitabilities"
+        );
+        assert_eq!(label("", LabelKey::Stats), "Stats");
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::store::JsonStore;
+
+/// The language each guild has chosen to fetch Pal data and label embeds in, optionally persisted to
+/// a JSON file.
+pub struct GuildLanguages {
+    languages: JsonStore<u64, String>,
+}
+
+impl GuildLanguages {
+    /// Create an empty store that is never persisted.
+    pub fn in_memory() -> Self {
+        Self {
+            languages: JsonStore::in_memory("guild languages"),
+        }
+    }
+
+    /// Create a store persisted to `path`, loading any choices already saved there.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            languages: JsonStore::load(path, "guild languages")?,
+        })
+    }
+
+    /// The language `guild_id` chose, if it chose one.
+    pub fn get(&self, guild_id: u64) -> Option<String> {
+        self.languages
+            .read(|languages| languages.get(&guild_id).cloned())
+    }
+
+    /// Sets `guild_id`'s language to `language`, or back to the default if `None`.
+    pub fn set(&self, guild_id: u64, language: Option<&str>) {
+        self.languages.update(|languages| match language {
+            Some(language) => languages.insert(guild_id, language.to_string()),
+            None => languages.remove(&guild_id),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let languages = GuildLanguages::in_memory();
+        assert_eq!(languages.get(1), None);
+
+        languages.set(1, Some("de"));
+        languages.set(2, Some("fr"));
+        assert_eq!(languages.get(1).as_deref(), Some("de"));
+        assert_eq!(languages.get(2).as_deref(), Some("fr"));
+
+        languages.set(1, None);
+        assert_eq!(languages.get(1), None);
+    }
+}
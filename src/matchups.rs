@@ -0,0 +1,118 @@
+/// Which defending types an attacking type is strong and weak against.
+pub struct TypeMatchup {
+    pub attacking: &'static str,
+    /// Types that take double damage from it.
+    pub strong: &'static [&'static str],
+    /// Types that take half damage from it.
+    pub weak: &'static [&'static str],
+}
+
+/// Palworld's element chart.
+pub const TYPE_CHART: &[TypeMatchup] = &[
+    TypeMatchup {
+        attacking: "Neutral",
+        strong: &[],
+        weak: &[],
+    },
+    TypeMatchup {
+        attacking: "Fire",
+        strong: &["Grass", "Ice"],
+        weak: &["Water"],
+    },
+    TypeMatchup {
+        attacking: "Water",
+        strong: &["Fire"],
+        weak: &["Electric"],
+    },
+    TypeMatchup {
+        attacking: "Grass",
+        strong: &["Ground"],
+        weak: &["Fire"],
+    },
+    TypeMatchup {
+        attacking: "Electric",
+        strong: &["Water"],
+        weak: &["Ground"],
+    },
+    TypeMatchup {
+        attacking: "Ground",
+        strong: &["Electric"],
+        weak: &["Grass"],
+    },
+    TypeMatchup {
+        attacking: "Ice",
+        strong: &["Dragon"],
+        weak: &["Fire"],
+    },
+    TypeMatchup {
+        attacking: "Dragon",
+        strong: &["Dark"],
+        weak: &["Ice"],
+    },
+    TypeMatchup {
+        attacking: "Dark",
+        strong: &["Neutral"],
+        weak: &["Dragon"],
+    },
+];
+
+/// How an attacking type fares against every defending type in a chart.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Matchups {
+    pub attacking: &'static str,
+    pub strong: Vec<&'static str>,
+    pub weak: Vec<&'static str>,
+    pub neutral: Vec<&'static str>,
+}
+
+/// How `attacking`, matched case-insensitively, fares against each type in `chart`, in chart
+/// order. `None` if the chart has no such type.
+pub fn attack_matchups(attacking: &str, chart: &[TypeMatchup]) -> Option<Matchups> {
+    let matchup = chart
+        .iter()
+        .find(|matchup| matchup.attacking.eq_ignore_ascii_case(attacking.trim()))?;
+
+    let mut matchups = Matchups {
+        attacking: matchup.attacking,
+        strong: Vec::new(),
+        weak: Vec::new(),
+        neutral: Vec::new(),
+    };
+    for defending in chart.iter().map(|defending| defending.attacking) {
+        if matchup.strong.contains(&defending) {
+            matchups.strong.push(defending);
+        } else if matchup.weak.contains(&defending) {
+            matchups.weak.push(defending);
+        } else {
+            matchups.neutral.push(defending);
+        }
+    }
+    Some(matchups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attack_matchups() {
+        let fire = attack_matchups("fire", TYPE_CHART).unwrap();
+        assert_eq!(fire.attacking, "Fire");
+        assert_eq!(fire.strong, vec!["Grass", "Ice"]);
+        assert_eq!(fire.weak, vec!["Water"]);
+        assert_eq!(
+            fire.neutral,
+            vec!["Neutral", "Fire", "Electric", "Ground", "Dragon", "Dark"]
+        );
+
+        assert_eq!(attack_matchups("Light", TYPE_CHART), None);
+    }
+
+    #[test]
+    fn test_attack_matchups_neutral() {
+        let neutral = attack_matchups("Neutral", TYPE_CHART).unwrap();
+        assert!(neutral.strong.is_empty());
+        assert!(neutral.weak.is_empty());
+        assert_eq!(neutral.neutral.len(), TYPE_CHART.len());
+    }
+}
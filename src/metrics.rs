@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::api::PalError;
+
+/// How often each command ran and failed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommandCounts {
+    pub invocations: u64,
+    pub failures: u64,
+}
+
+/// How a call to the Pal API turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiOutcome {
+    Success,
+    Unauthorized,
+    NotFound,
+    Error,
+}
+
+impl ApiOutcome {
+    /// The outcome of a call that returned `result`.
+    pub fn of<T>(result: &Result<T, PalError>) -> Self {
+        match result {
+            Ok(_) => Self::Success,
+            Err(PalError::TokenExpired) => Self::Unauthorized,
+            Err(PalError::NoPalFound(_)) => Self::NotFound,
+            Err(_) => Self::Error,
+        }
+    }
+}
+
+/// How many calls to the Pal API were made, by outcome.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ApiCounts {
+    pub requests: u64,
+    pub successes: u64,
+    pub unauthorized: u64,
+    pub not_found: u64,
+    pub errors: u64,
+}
+
+/// Counters of what the bot has done since it started.
+#[derive(Default)]
+pub struct Metrics {
+    commands: Mutex<BTreeMap<String, CommandCounts>>,
+    api: Mutex<ApiCounts>,
+    pal_errors: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+impl Metrics {
+    /// Records that `command` was invoked.
+    pub fn record_invocation(&self, command: &str) {
+        self.commands
+            .lock()
+            .unwrap()
+            .entry(command.to_string())
+            .or_default()
+            .invocations += 1;
+    }
+
+    /// Records that `command` failed, and whether it failed looking up a Pal.
+    pub fn record_failure(&self, command: &str, pal_error: bool) {
+        self.commands
+            .lock()
+            .unwrap()
+            .entry(command.to_string())
+            .or_default()
+            .failures += 1;
+        if pal_error {
+            self.pal_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records a lookup answered from the cache, if `hit`, or fetched from the API otherwise.
+    pub fn record_cache(&self, hit: bool) {
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a call to the Pal API that ended in `outcome`.
+    pub fn record_api(&self, outcome: ApiOutcome) {
+        let mut api = self.api.lock().unwrap();
+        api.requests += 1;
+        match outcome {
+            ApiOutcome::Success => api.successes += 1,
+            ApiOutcome::Unauthorized => api.unauthorized += 1,
+            ApiOutcome::NotFound => api.not_found += 1,
+            ApiOutcome::Error => api.errors += 1,
+        }
+    }
+
+    /// The calls to the Pal API made so far.
+    pub fn api(&self) -> ApiCounts {
+        *self.api.lock().unwrap()
+    }
+
+    /// The counts for each command invoked so far, by name.
+    pub fn commands(&self) -> BTreeMap<String, CommandCounts> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    /// Summarizes every counter, one per line.
+    pub fn report(&self) -> String {
+        let mut lines = self
+            .commands()
+            .into_iter()
+            .map(|(name, counts)| {
+                format!(
+                    "`{name}`: {} runs, {} failed",
+                    counts.invocations, counts.failures
+                )
+            })
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            lines.push("No commands run yet".to_string());
+        }
+
+        let hits = self.cache_hits.load(Ordering::Relaxed);
+        let misses = self.cache_misses.load(Ordering::Relaxed);
+        let hit_rate = match hits + misses {
+            0 => "-".to_string(),
+            total => format!("{:.0}%", hits as f64 * 100.0 / total as f64),
+        };
+        lines.push(format!("API: {}", format_api_counts(&self.api())));
+        lines.push(format!(
+            "Pal lookup errors: {}",
+            self.pal_errors.load(Ordering::Relaxed)
+        ));
+        lines.push(format!(
+            "Cache: {hits} hits, {misses} misses ({hit_rate} hit rate)"
+        ));
+
+        lines.join("\n")
+    }
+}
+
+/// Summarizes `api` on one line.
+pub fn format_api_counts(api: &ApiCounts) -> String {
+    format!(
+        "{} requests, {} ok, {} unauthorized, {} not found, {} errors",
+        api.requests, api.successes, api.unauthorized, api.not_found, api.errors
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_counts() {
+        let metrics = Metrics::default();
+        let results: [Result<(), PalError>; 5] = [
+            Ok(()),
+            Ok(()),
+            Err(PalError::TokenExpired),
+            Err(PalError::NoPalFound("Lambal".to_string())),
+            Err(PalError::Timeout),
+        ];
+        for result in &results {
+            metrics.record_api(ApiOutcome::of(result));
+        }
+
+        assert_eq!(
+            metrics.api(),
+            ApiCounts {
+                requests: 5,
+                successes: 2,
+                unauthorized: 1,
+                not_found: 1,
+                errors: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let metrics = Metrics::default();
+        assert_eq!(
+            metrics.report(),
+            "No commands run yet\n\
+             API: 0 requests, 0 ok, 0 unauthorized, 0 not found, 0 errors\n\
+             Pal lookup errors: 0\n\
+             Cache: 0 hits, 0 misses (- hit rate)"
+        );
+
+        metrics.record_invocation("pal");
+        metrics.record_invocation("pal");
+        metrics.record_invocation("random");
+        metrics.record_failure("pal", true);
+        metrics.record_failure("random", false);
+        metrics.record_cache(true);
+        metrics.record_cache(false);
+        metrics.record_cache(false);
+        metrics.record_cache(false);
+
+        assert_eq!(
+            metrics.commands()["pal"],
+            CommandCounts {
+                invocations: 2,
+                failures: 1,
+            }
+        );
+        assert_eq!(
+            metrics.report(),
+            "`pal`: 2 runs, 1 failed\n\
+             `random`: 1 runs, 1 failed\n\
+             API: 0 requests, 0 ok, 0 unauthorized, 0 not found, 0 errors\n\
+             Pal lookup errors: 1\n\
+             Cache: 1 hits, 3 misses (25% hit rate)"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::ops::Range;
+
+/// The most fields Discord allows in one embed, and so the most items on a page.
+pub const MAX_PAGE_SIZE: usize = 25;
+
+/// One page of a paginated list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// The page's number, starting at 1.
+    pub number: usize,
+    pub total_pages: usize,
+    /// The indices of the items on the page.
+    pub items: Range<usize>,
+}
+
+/// Clamps a configured page size to what fits in one embed.
+pub fn clamp_page_size(size: usize) -> usize {
+    size.clamp(1, MAX_PAGE_SIZE)
+}
+
+/// The number of pages needed for `count` items, `size` to a page. An empty list still has one
+/// (empty) page.
+pub fn total_pages(count: usize, size: usize) -> usize {
+    count.div_ceil(size).max(1)
+}
+
+/// Page `number` of `count` items split `size` to a page, or `None` if there is no such page.
+pub fn page(count: usize, size: usize, number: usize) -> Option<Page> {
+    let total_pages = total_pages(count, size);
+    if number == 0 || number > total_pages {
+        return None;
+    }
+
+    let start = (number - 1) * size;
+    Some(Page {
+        number,
+        total_pages,
+        items: start..count.min(start + size),
+    })
+}
+
+/// The index of the page after (or, if not `forward`, before) page `current` of `total`, wrapping
+/// around at either end.
+pub fn turn(current: usize, total: usize, forward: bool) -> usize {
+    if total == 0 {
+        return 0;
+    }
+    if forward {
+        (current + 1) % total
+    } else {
+        current.checked_sub(1).unwrap_or(total - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_pages() {
+        let cases = [
+            (0, 25, 1),
+            (1, 25, 1),
+            (25, 25, 1),
+            (26, 25, 2),
+            (111, 10, 12),
+            (111, 1, 111),
+            (100, 7, 15),
+        ];
+
+        for (count, size, expected) in cases {
+            assert_eq!(
+                total_pages(count, size),
+                expected,
+                "{count} items, {size} a page"
+            );
+        }
+    }
+
+    #[test]
+    fn test_page() {
+        assert_eq!(
+            page(111, 25, 5),
+            Some(Page {
+                number: 5,
+                total_pages: 5,
+                items: 100..111,
+            })
+        );
+        assert_eq!(page(111, 10, 12).unwrap().items, 110..111);
+        assert_eq!(page(100, 7, 15).unwrap().items, 98..100);
+        assert_eq!(page(50, 25, 2).unwrap().items, 25..50);
+        assert_eq!(page(0, 25, 1).unwrap().items, 0..0);
+        assert_eq!(page(111, 25, 0), None);
+        assert_eq!(page(111, 25, 6), None);
+    }
+
+    #[test]
+    fn test_clamp_page_size() {
+        assert_eq!(clamp_page_size(0), 1);
+        assert_eq!(clamp_page_size(10), 10);
+        assert_eq!(clamp_page_size(100), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_turn() {
+        assert_eq!(turn(0, 3, true), 1);
+        assert_eq!(turn(2, 3, true), 0);
+        assert_eq!(turn(1, 3, false), 0);
+        assert_eq!(turn(0, 3, false), 2);
+        assert_eq!(turn(0, 1, true), 0);
+        assert_eq!(turn(0, 1, false), 0);
+        assert_eq!(turn(0, 0, true), 0);
+    }
+}
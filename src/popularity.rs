@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::store::JsonStore;
+
+/// Counts how often each Pal is looked up, optionally persisted to a JSON file.
+pub struct Popularity {
+    counts: JsonStore<String, u64>,
+}
+
+impl Popularity {
+    /// Create an empty counter that is never persisted.
+    pub fn in_memory() -> Self {
+        Self {
+            counts: JsonStore::in_memory("lookup counts"),
+        }
+    }
+
+    /// Create a counter persisted to `path`, loading any counts already saved there.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        Ok(Self {
+            counts: JsonStore::load(path, "lookup counts")?,
+        })
+    }
+
+    /// Records a lookup of `name`.
+    pub fn record(&self, name: &str) {
+        self.counts
+            .update(|counts| *counts.entry(name.to_string()).or_default() += 1);
+    }
+
+    /// The `n` most looked-up Pals with their counts, most popular first.
+    pub fn top(&self, n: usize) -> Vec<(String, u64)> {
+        let mut counts = self.counts.read(|counts| {
+            counts
+                .iter()
+                .map(|(name, count)| (name.clone(), *count))
+                .collect::<Vec<_>>()
+        });
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(n);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top() {
+        let popularity = Popularity::in_memory();
+        for name in [
+            "Lamball", "Cattiva", "Lamball", "Chikipi", "Cattiva", "Lamball",
+        ] {
+            popularity.record(name);
+        }
+
+        assert_eq!(
+            popularity.top(2),
+            vec![("Lamball".to_string(), 3), ("Cattiva".to_string(), 2)]
+        );
+        assert_eq!(popularity.top(10).len(), 3);
+    }
+
+    #[test]
+    fn test_top_ties() {
+        let popularity = Popularity::in_memory();
+        popularity.record("Lifmunk");
+        popularity.record("Foxparks");
+
+        assert_eq!(
+            popularity.top(2),
+            vec![("Foxparks".to_string(), 1), ("Lifmunk".to_string(), 1)]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How many users' buckets are kept before the full ones are forgotten.
+const MAX_TRACKED_USERS: usize = 1024;
+
+/// One user's allowance: how many commands they may still run, as of when it was last updated.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A token bucket per user: each may run `capacity` commands at once, and earns them back
+/// steadily over `window`.
+pub struct RateLimiter {
+    capacity: u32,
+    window: Duration,
+    buckets: Mutex<HashMap<u64, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `capacity` commands per user every `window`. A capacity of zero
+    /// allows everything.
+    pub fn new(capacity: u32, window: Duration) -> Self {
+        Self {
+            capacity,
+            window,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes one command from `user_id`'s allowance, or returns how long until they have one.
+    pub fn check(&self, user_id: u64) -> Result<(), Duration> {
+        self.check_at(user_id, Instant::now())
+    }
+
+    /// Like [`RateLimiter::check`], as of `now`.
+    fn check_at(&self, user_id: u64, now: Instant) -> Result<(), Duration> {
+        if self.capacity == 0 || self.window.is_zero() {
+            return Ok(());
+        }
+
+        let capacity = f64::from(self.capacity);
+        let per_second = capacity / self.window.as_secs_f64();
+        let refilled = |bucket: &Bucket| {
+            let earned = now.saturating_duration_since(bucket.updated).as_secs_f64() * per_second;
+            (bucket.tokens + earned).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_USERS {
+            buckets.retain(|_, bucket| refilled(bucket) < capacity);
+        }
+
+        let bucket = buckets.entry(user_id).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        bucket.tokens = refilled(bucket);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_refills() {
+        let limiter = RateLimiter::new(5, Duration::from_secs(10));
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            assert_eq!(limiter.check_at(1, start), Ok(()));
+        }
+        assert_eq!(limiter.check_at(1, start), Err(Duration::from_secs(2)));
+        assert_eq!(limiter.check_at(2, start), Ok(()));
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check_at(1, later), Err(Duration::from_secs(1)));
+
+        let later = start + Duration::from_secs(2);
+        assert_eq!(limiter.check_at(1, later), Ok(()));
+        assert!(limiter.check_at(1, later).is_err());
+
+        let much_later = start + Duration::from_secs(60);
+        for _ in 0..5 {
+            assert_eq!(limiter.check_at(1, much_later), Ok(()));
+        }
+        assert!(limiter.check_at(1, much_later).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let limiter = RateLimiter::new(0, Duration::from_secs(10));
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(limiter.check_at(1, now), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_forgets_full_buckets() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(1));
+        let start = Instant::now();
+        for user in 0..MAX_TRACKED_USERS as u64 {
+            limiter.check_at(user, start).unwrap();
+        }
+
+        limiter
+            .check_at(u64::MAX, start + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+/// Data read from a JSON file that can be reread while the bot runs. A reload that fails leaves
+/// the data already loaded in place.
+pub struct Reloadable<T> {
+    path: Option<PathBuf>,
+    current: RwLock<Arc<T>>,
+}
+
+impl<T: Default + DeserializeOwned> Reloadable<T> {
+    /// Create an empty value with no file behind it.
+    pub fn empty() -> Self {
+        Self {
+            path: None,
+            current: RwLock::new(Arc::new(T::default())),
+        }
+    }
+
+    /// Create a value loaded from `path`.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let current = read(&path)?;
+        Ok(Self {
+            path: Some(path),
+            current: RwLock::new(Arc::new(current)),
+        })
+    }
+
+    /// The data as last loaded.
+    pub fn get(&self) -> Arc<T> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Rereads the file and swaps in its contents. Returns `false` if there is no file to reread,
+    /// and an error, keeping the current data, if the file can't be read or parsed.
+    pub fn reload(&self) -> Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+
+        let reloaded = read(path)?;
+        *self.current.write().unwrap() = Arc::new(reloaded);
+        Ok(true)
+    }
+}
+
+fn read<T: DeserializeOwned>(path: &PathBuf) -> Result<T> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Couldn't parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_reload() {
+        let path = std::env::temp_dir().join(format!("palbot-reload-{}.json", std::process::id()));
+        fs::write(&path, r#"{"a": 1}"#).unwrap();
+
+        let reloadable = Reloadable::<HashMap<String, u32>>::load(path.clone()).unwrap();
+        assert_eq!(reloadable.get()["a"], 1);
+
+        fs::write(&path, r#"{"a": 2, "b": 3}"#).unwrap();
+        assert!(reloadable.reload().unwrap());
+        assert_eq!(reloadable.get()["a"], 2);
+        assert_eq!(reloadable.get()["b"], 3);
+
+        fs::write(&path, r#"{"a": "#).unwrap();
+        let err = reloadable.reload().unwrap_err();
+        assert!(err.to_string().starts_with("Couldn't parse"));
+        assert_eq!(reloadable.get()["a"], 2);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_reload_without_file() {
+        let reloadable = Reloadable::<HashMap<String, u32>>::empty();
+        assert!(!reloadable.reload().unwrap());
+        assert!(reloadable.get().is_empty());
+    }
+}
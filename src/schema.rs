@@ -0,0 +1,76 @@
+use std::collections::BTreeSet;
+
+use log::warn;
+use serde_json::Value;
+
+use crate::api::Pal;
+
+/// The JSON keys `Pal` reads, as the API spells them.
+fn expected_fields() -> BTreeSet<String> {
+    match serde_json::to_value(Pal::default()) {
+        Ok(Value::Object(fields)) => fields.into_iter().map(|(key, _)| key).collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+/// The keys of the Pal object `raw` that `Pal` doesn't read, hinting the API has grown data the
+/// bot ignores.
+pub fn detect_unknown_fields(raw: &Value) -> Vec<String> {
+    let expected = expected_fields();
+    raw.as_object()
+        .into_iter()
+        .flat_map(|object| object.keys())
+        .filter(|key| !expected.contains(*key))
+        .cloned()
+        .collect()
+}
+
+/// The keys `Pal` reads that the Pal object `raw` lacks, and which therefore decode as defaults.
+pub fn detect_missing_fields(raw: &Value) -> Vec<String> {
+    let Some(object) = raw.as_object() else {
+        return vec![];
+    };
+    expected_fields()
+        .into_iter()
+        .filter(|key| !object.contains_key(key))
+        .collect()
+}
+
+/// Warns about any difference between the Pal object `raw` and the fields `Pal` expects.
+pub fn warn_schema_drift(raw: &Value) {
+    let unknown = detect_unknown_fields(raw);
+    if !unknown.is_empty() {
+        warn!(
+            "The Pal API sent fields the bot ignores: {}",
+            unknown.join(", ")
+        );
+    }
+
+    let missing = detect_missing_fields(raw);
+    if !missing.is_empty() {
+        warn!(
+            "The Pal API didn't send these fields, so they are left empty: {}",
+            missing.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_unknown_fields() {
+        let mut raw = serde_json::to_value(Pal::default()).unwrap();
+        assert!(detect_unknown_fields(&raw).is_empty());
+        assert!(detect_missing_fields(&raw).is_empty());
+
+        raw["rarity"] = 3.into();
+        raw["partnerSkill"] = "Fluffy Shield".into();
+        raw.as_object_mut().unwrap().remove("breeding");
+
+        assert_eq!(detect_unknown_fields(&raw), vec!["partnerSkill", "rarity"]);
+        assert_eq!(detect_missing_fields(&raw), vec!["breeding"]);
+        assert!(detect_unknown_fields(&Value::Null).is_empty());
+    }
+}
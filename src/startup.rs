@@ -0,0 +1,226 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use log::warn;
+use serenity::gateway::GatewayError;
+use serenity::http::HttpError;
+
+/// Why the Discord client failed to start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupFailure {
+    InvalidToken,
+    Intents,
+    Network,
+    Other,
+}
+
+impl StartupFailure {
+    /// Classifies an error returned while starting the client.
+    pub fn classify(err: &serenity::Error) -> Self {
+        match err {
+            serenity::Error::Gateway(
+                GatewayError::InvalidAuthentication | GatewayError::NoAuthentication,
+            ) => Self::InvalidToken,
+            serenity::Error::Gateway(
+                GatewayError::InvalidGatewayIntents | GatewayError::DisallowedGatewayIntents,
+            ) => Self::Intents,
+            serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+                if response.status_code == reqwest::StatusCode::UNAUTHORIZED =>
+            {
+                Self::InvalidToken
+            }
+            serenity::Error::Gateway(_)
+            | serenity::Error::Http(HttpError::Request(_))
+            | serenity::Error::Io(_)
+            | serenity::Error::Tungstenite(_) => Self::Network,
+            _ => Self::Other,
+        }
+    }
+
+    /// Whether retrying the startup could succeed.
+    pub fn is_transient(self) -> bool {
+        self == Self::Network
+    }
+
+    /// Actionable advice for the operator.
+    pub fn guidance(self) -> &'static str {
+        match self {
+            Self::InvalidToken => "Discord rejected the bot token; check DISCORD_TOKEN",
+            Self::Intents => {
+                "Discord rejected the gateway intents; enable Message Content for the bot"
+            }
+            Self::Network => "Could not reach Discord; check the network connection",
+            Self::Other => "Unexpected error while starting the client",
+        }
+    }
+}
+
+/// Explains why `setup` failed to load the Pal catalog or register commands, naming the setting
+/// most likely at fault.
+pub fn explain_setup_error(err: &anyhow::Error, pal_api_url: &str) -> String {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            if err.is_timeout() {
+                return format!(
+                    "The Pal API at {pal_api_url} didn't respond in time; check that it is running, \
+                     or raise PAL_API_TIMEOUT_SECS"
+                );
+            }
+            if err.is_connect() {
+                return format!(
+                    "Couldn't connect to the Pal API at {pal_api_url}; check PAL_API_URL and that \
+                     the API is running"
+                );
+            }
+        }
+
+        let undecodable = cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_decode);
+        if undecodable || cause.is::<serde_json::Error>() {
+            return format!(
+                "{pal_api_url} didn't answer like a Paldex API; check that PAL_API_URL is the \
+                 API's base URL"
+            );
+        }
+
+        if let Some(err) = cause.downcast_ref::<serenity::Error>() {
+            return format!("Couldn't register the slash commands with Discord: {err}");
+        }
+    }
+
+    format!("Setup failed: {err:#}")
+}
+
+type StartFuture<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+
+/// Runs `op` on `target` up to `attempts` times, backing off exponentially from `base_delay`
+/// between transient failures. Non-transient failures are returned immediately.
+pub async fn retry_startup<S, T, E: std::fmt::Display>(
+    target: &mut S,
+    attempts: u32,
+    base_delay: Duration,
+    is_transient: impl Fn(&E) -> bool,
+    mut op: impl FnMut(&mut S) -> StartFuture<'_, T, E>,
+) -> Result<T, E> {
+    let mut attempt = 1;
+    loop {
+        match op(target).await {
+            Err(err) if attempt < attempts && is_transient(&err) => {
+                let delay = base_delay * 2u32.pow(attempt - 1);
+                warn!("Startup attempt {attempt}/{attempts} failed: {err}; retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::MockServer;
+
+    #[tokio::test]
+    async fn test_explain_setup_error() {
+        let refused = reqwest::get("http://127.0.0.1:1/").await.unwrap_err();
+        assert!(explain_setup_error(&refused.into(), "http://127.0.0.1:1/")
+            .starts_with("Couldn't connect to the Pal API at http://127.0.0.1:1/"));
+
+        let server = MockServer::start(vec![(200, "<html></html>".to_string())]).await;
+        let garbled = reqwest::get(server.url())
+            .await
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_err();
+        let err = anyhow::Error::from(garbled).context("loading the catalog");
+        assert!(explain_setup_error(&err, server.url()).contains("didn't answer like a Paldex API"));
+
+        let other = anyhow::anyhow!("disk full");
+        assert_eq!(explain_setup_error(&other, "x"), "Setup failed: disk full");
+    }
+
+    #[tokio::test]
+    async fn test_retry_startup_recovers_from_transient_failure() {
+        let mut calls = 0;
+        let result = retry_startup(
+            &mut calls,
+            3,
+            Duration::from_millis(1),
+            |_| true,
+            |calls| {
+                *calls += 1;
+                let attempt = *calls;
+                Box::pin(async move {
+                    if attempt == 1 {
+                        Err("network down".to_string())
+                    } else {
+                        Ok(attempt)
+                    }
+                })
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok(2));
+    }
+
+    #[tokio::test]
+    async fn test_retry_startup_gives_up() {
+        let mut calls = 0;
+        let result: Result<(), String> = retry_startup(
+            &mut calls,
+            3,
+            Duration::from_millis(1),
+            |_| true,
+            |calls| {
+                *calls += 1;
+                Box::pin(async { Err("network down".to_string()) })
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_startup_stops_on_fatal_failure() {
+        let mut calls = 0;
+        let result: Result<(), String> = retry_startup(
+            &mut calls,
+            3,
+            Duration::from_millis(1),
+            |_| false,
+            |calls| {
+                *calls += 1;
+                Box::pin(async { Err("bad token".to_string()) })
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            StartupFailure::classify(&GatewayError::InvalidAuthentication.into()),
+            StartupFailure::InvalidToken
+        );
+        assert_eq!(
+            StartupFailure::classify(&GatewayError::DisallowedGatewayIntents.into()),
+            StartupFailure::Intents
+        );
+        assert_eq!(
+            StartupFailure::classify(&GatewayError::HeartbeatFailed.into()),
+            StartupFailure::Network
+        );
+        assert!(!StartupFailure::InvalidToken.is_transient());
+        assert!(StartupFailure::Network.is_transient());
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use log::error;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A map kept in memory and, optionally, saved to a JSON file after every change.
+pub struct JsonStore<K, V> {
+    entries: Mutex<Versioned<K, V>>,
+    file: Option<Arc<StoreFile>>,
+    /// What the map holds, like "lookup counts", for log messages.
+    what: &'static str,
+}
+
+/// A map, with how many times it has changed.
+struct Versioned<K, V> {
+    map: HashMap<K, V>,
+    version: u64,
+}
+
+/// The file a [`JsonStore`] is saved to.
+struct StoreFile {
+    path: PathBuf,
+    /// The version of the map last saved. Held while saving, so saves don't interleave.
+    saved: Mutex<u64>,
+}
+
+impl StoreFile {
+    /// Saves `map` as of `version`, unless a later version has been saved already.
+    fn save(&self, map: &impl Serialize, version: u64, what: &str) {
+        let mut saved = self.saved.lock().unwrap();
+        if *saved >= version {
+            return;
+        }
+
+        // Written beside the file and moved over it, so readers never see half a save.
+        let partial = self.path.with_extension("partial");
+        let written = serde_json::to_string(map)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| fs::write(&partial, json).map_err(anyhow::Error::from))
+            .and_then(|()| fs::rename(&partial, &self.path).map_err(anyhow::Error::from));
+        match written {
+            Ok(()) => *saved = version,
+            Err(err) => error!("Error saving {what} to {}: {err:?}", self.path.display()),
+        }
+    }
+}
+
+impl<K, V> JsonStore<K, V>
+where
+    K: Eq + Hash + Clone + Serialize + DeserializeOwned + Send + 'static,
+    V: Clone + Serialize + DeserializeOwned + Send + 'static,
+{
+    /// Create an empty store of `what` that is never saved.
+    pub fn in_memory(what: &'static str) -> Self {
+        Self {
+            entries: Mutex::new(Versioned {
+                map: HashMap::new(),
+                version: 0,
+            }),
+            file: None,
+            what,
+        }
+    }
+
+    /// Create a store of `what` saved to `path`, loading anything already saved there.
+    pub fn load(path: PathBuf, what: &'static str) -> Result<Self> {
+        let map = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            entries: Mutex::new(Versioned { map, version: 0 }),
+            file: Some(Arc::new(StoreFile {
+                path,
+                saved: Mutex::new(0),
+            })),
+            what,
+        })
+    }
+
+    /// Reads the map with `read`.
+    pub fn read<R>(&self, read: impl FnOnce(&HashMap<K, V>) -> R) -> R {
+        read(&self.entries.lock().unwrap().map)
+    }
+
+    /// Changes the map with `change`, then saves it if the store has a file. The map is saved
+    /// from a copy, after the store is unlocked, and on a blocking thread when called from the
+    /// async runtime.
+    pub fn update<R>(&self, change: impl FnOnce(&mut HashMap<K, V>) -> R) -> R {
+        let (result, snapshot) = {
+            let mut entries = self.entries.lock().unwrap();
+            let result = change(&mut entries.map);
+            entries.version += 1;
+            let snapshot = self
+                .file
+                .is_some()
+                .then(|| (entries.map.clone(), entries.version));
+            (result, snapshot)
+        };
+
+        if let (Some(file), Some((map, version))) = (&self.file, snapshot) {
+            let file = file.clone();
+            let what = self.what;
+            let save = move || file.save(&map, version, what);
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => drop(runtime.spawn_blocking(save)),
+                Err(_) => save(),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory() {
+        let store = JsonStore::<String, u64>::in_memory("counts");
+        store.update(|counts| counts.insert("Lamball".to_string(), 1));
+
+        assert_eq!(store.read(|counts| counts.get("Lamball").copied()), Some(1));
+    }
+
+    #[test]
+    fn test_persisted() {
+        let path = std::env::temp_dir().join(format!("palbot-store-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let store = JsonStore::<u64, String>::load(path.clone(), "languages").unwrap();
+        store.update(|languages| languages.insert(42, "ja".to_string()));
+
+        let reloaded = JsonStore::<u64, String>::load(path.clone(), "languages").unwrap();
+        assert_eq!(
+            reloaded.read(|languages| languages.get(&42).cloned()),
+            Some("ja".to_string())
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_persisted_from_runtime() {
+        let path =
+            std::env::temp_dir().join(format!("palbot-store-async-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let store = JsonStore::<String, u64>::load(path.clone(), "counts").unwrap();
+        for _ in 0..20 {
+            store.update(|counts| *counts.entry("Lamball".to_string()).or_default() += 1);
+        }
+
+        let mut saved = None;
+        for _ in 0..100 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            let reloaded = JsonStore::<String, u64>::load(path.clone(), "counts").unwrap();
+            saved = reloaded.read(|counts| counts.get("Lamball").copied());
+            if saved == Some(20) {
+                break;
+            }
+        }
+        assert_eq!(saved, Some(20));
+
+        fs::remove_file(path).unwrap();
+    }
+}
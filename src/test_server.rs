@@ -0,0 +1,132 @@
+//! A tiny HTTP/1.1 server for exercising the API client in tests.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::api::{APIResponse, Pal};
+
+/// Serves canned responses in order, repeating the last one once the queue is exhausted.
+pub struct MockServer {
+    url: String,
+    connections: Arc<AtomicUsize>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+struct Shared {
+    responses: Mutex<VecDeque<(u16, String)>>,
+    connections: Arc<AtomicUsize>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    /// Starts a server on a random local port answering with `responses`.
+    pub async fn start(responses: Vec<(u16, String)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let connections = Arc::new(AtomicUsize::new(0));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let shared = Arc::new(Shared {
+            responses: Mutex::new(responses.into()),
+            connections: connections.clone(),
+            requests: requests.clone(),
+        });
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                shared.connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve(stream, shared.clone()));
+            }
+        });
+
+        Self {
+            url,
+            connections,
+            requests,
+        }
+    }
+
+    /// The base URL of the server, with a trailing slash.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The number of TCP connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// The raw request heads received so far, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// An API response holding every Pal in `names`, numbered from 1, on a single page.
+pub fn response_json(names: &[&str]) -> String {
+    page_json(names, 1, names.len())
+}
+
+/// An API response holding `page` of `total` Pals, with the Pals in `names` numbered from 1.
+pub fn page_json(names: &[&str], page: i64, total: usize) -> String {
+    let content = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| Pal {
+            id: i as i64 + 1,
+            name: (*name).to_string(),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string(&APIResponse {
+        count: content.len() as i64,
+        total: total as i64,
+        page,
+        content,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+async fn serve(mut stream: TcpStream, shared: Arc<Shared>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    loop {
+        let head_end = loop {
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+        buf.drain(..head_end);
+        shared.requests.lock().unwrap().push(head);
+
+        let (status, body) = {
+            let mut responses = shared.responses.lock().unwrap();
+            if responses.len() > 1 {
+                responses.pop_front().unwrap()
+            } else {
+                responses.front().cloned().unwrap_or((404, String::new()))
+            }
+        };
+
+        let response = format!(
+            "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! A small read-only web view of Pals, for linking to one outside Discord.
+//!
+//! Only built with the `web` feature, and only served when `WEB_ADDR` is set.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use inflector::Inflector;
+use log::{debug, error, info};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use urlencoding::decode;
+
+use crate::api::{Pal, PalApi, PalError};
+use crate::format_level;
+
+/// The most bytes read of a request head before giving up on it.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// How long a client has to send its request head before the connection is dropped.
+const HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Escapes `text` for use in HTML text and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders `pal` as a standalone HTML page showing what its embed shows.
+pub fn render_pal_html(pal: &Pal) -> String {
+    let name = escape_html(&pal.name);
+    let types = pal
+        .types
+        .iter()
+        .map(|typ| escape_html(&typ.to_title_case()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let suitabilities = pal
+        .suitability
+        .iter()
+        .map(|suitability| {
+            format!(
+                "<li>{} {}</li>",
+                escape_html(&suitability.type_field.to_title_case()),
+                format_level(suitability.level)
+            )
+        })
+        .collect::<String>();
+    let drops = pal
+        .drops
+        .iter()
+        .map(|drop| format!("<li>{}</li>", escape_html(&drop.to_title_case())))
+        .collect::<String>();
+
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head><meta charset=\"utf-8\"><title>{name}</title></head>\n\
+         <body>\n\
+         <h1>{name} <small>#{id}</small></h1>\n\
+         <img src=\"{image}\" alt=\"{name}\">\n\
+         <p>{description}</p>\n\
+         <p><a href=\"{wiki}\">Wiki</a></p>\n\
+         <h2>Types</h2>\n<p>{types}</p>\n\
+         <h2>Aura</h2>\n<p><strong>{aura}</strong>: {aura_description}</p>\n\
+         <h2>Work Suitability</h2>\n<ul>{suitabilities}</ul>\n\
+         <h2>Drops</h2>\n<ul>{drops}</ul>\n\
+         </body>\n\
+         </html>\n",
+        id = pal.id,
+        image = escape_html(&pal.image),
+        description = escape_html(&pal.description),
+        wiki = escape_html(&pal.wiki),
+        aura = escape_html(&pal.aura.name.to_title_case()),
+        aura_description = escape_html(&pal.aura.description),
+    )
+}
+
+/// The Pal name asked for by a request for `path`, if it has the form `/pal/<name>`.
+fn requested_name(path: &str) -> Option<String> {
+    let name = path.strip_prefix("/pal/")?;
+    let name = name.split(['?', '#']).next().unwrap_or_default();
+    let name = decode(name).ok()?.trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// A simple HTML page carrying `message`.
+fn message_page(message: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\"><body><p>{}</p></body></html>\n",
+        escape_html(message)
+    )
+}
+
+/// The status, reason and body answering the request whose first line is `request_line`, with Pal
+/// data fetched in `language`.
+async fn respond(
+    api: &dyn PalApi,
+    language: &str,
+    request_line: &str,
+) -> (u16, &'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return (400, "Bad Request", message_page("Bad request"));
+    };
+    if method != "GET" {
+        return (
+            405,
+            "Method Not Allowed",
+            message_page("Only GET is supported"),
+        );
+    }
+    let Some(name) = requested_name(path) else {
+        return (404, "Not Found", message_page("Try /pal/<name>"));
+    };
+
+    match api.get_pals_named(&name, language).await {
+        Ok(pals) => (200, "OK", render_pal_html(&pals[0])),
+        Err(err @ PalError::NoPalFound(_)) => (404, "Not Found", message_page(&err.to_string())),
+        Err(err) => {
+            error!("Error serving /pal/{name}: {err}");
+            (502, "Bad Gateway", message_page("Couldn't fetch that Pal"))
+        }
+    }
+}
+
+/// Reads from `stream` up to the end of the request head, or `None` if the client hangs up or
+/// sends too much.
+async fn read_head(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return None;
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => head.extend_from_slice(&chunk[..n]),
+        }
+    }
+    Some(head)
+}
+
+/// Answers one request on `stream` in `language`, then closes it. Clients that take longer than
+/// `head_timeout` to send the request head are dropped unanswered.
+async fn handle(
+    mut stream: TcpStream,
+    api: Arc<dyn PalApi>,
+    language: Arc<str>,
+    head_timeout: Duration,
+) {
+    let head = match tokio::time::timeout(head_timeout, read_head(&mut stream)).await {
+        Ok(Some(head)) => head,
+        Ok(None) => return,
+        Err(_) => {
+            debug!("Dropped a web client that sent no request in {head_timeout:?}");
+            return;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&head);
+    let request_line = head.lines().next().unwrap_or_default();
+    debug!("Web request: {request_line}");
+    let (status, reason, body) = respond(api.as_ref(), &language, request_line).await;
+
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: text/html; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Serves `/pal/<name>` pages from `listener`, fetching Pals in `language` from `api`, until the
+/// process exits.
+pub async fn serve(listener: TcpListener, api: Arc<dyn PalApi>, language: String) {
+    let language = Arc::<str>::from(language);
+    if let Ok(addr) = listener.local_addr() {
+        info!("Serving Pal pages on http://{addr}/pal/<name>");
+    }
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(handle(stream, api.clone(), language.clone(), HEAD_TIMEOUT));
+            }
+            Err(err) => error!("Error accepting a web connection: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Aura, Suitability};
+
+    #[test]
+    fn test_render_pal_html() {
+        let pal = Pal {
+            id: 1,
+            name: "Lamball <script>alert('hi')</script>".to_string(),
+            description: "Soft & \"fluffy\"".to_string(),
+            types: vec!["neutral".to_string()],
+            suitability: vec![Suitability {
+                type_field: "handiwork".to_string(),
+                level: 1,
+            }],
+            drops: vec!["lamball_mutton".to_string()],
+            aura: Aura {
+                name: "fluffy_shield".to_string(),
+                description: "Deploys a shield".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let html = render_pal_html(&pal);
+        assert!(html.contains(
+            "<h1>Lamball &lt;script&gt;alert(&#39;hi&#39;)&lt;/script&gt; <small>#1</small></h1>"
+        ));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<p>Soft &amp; &quot;fluffy&quot;</p>"));
+        assert!(html.contains("<p>Neutral</p>"));
+        assert!(html.contains("<li>Handiwork 1</li>"));
+        assert!(html.contains("<li>Lamball Mutton</li>"));
+        assert!(html.contains("<strong>Fluffy Shield</strong>: Deploys a shield"));
+    }
+
+    #[test]
+    fn test_requested_name() {
+        assert_eq!(requested_name("/pal/Lamball"), Some("Lamball".to_string()));
+        assert_eq!(
+            requested_name("/pal/Jormuntide%20Ignis?x=1"),
+            Some("Jormuntide Ignis".to_string())
+        );
+        assert_eq!(requested_name("/pal/"), None);
+        assert_eq!(requested_name("/favicon.ico"), None);
+    }
+
+    #[tokio::test]
+    async fn test_silent_client_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let api = crate::api::HttpPalApi::new(
+            reqwest::Url::parse("http://127.0.0.1:1").unwrap(),
+            reqwest::Client::new(),
+            0,
+            Duration::MAX,
+        );
+        tokio::spawn(handle(
+            stream,
+            Arc::new(api),
+            Arc::from("en"),
+            Duration::from_millis(50),
+        ));
+
+        let mut buf = [0u8; 16];
+        let read = tokio::time::timeout(Duration::from_secs(5), client.read(&mut buf))
+            .await
+            .expect("the server should hang up on a silent client");
+        assert_eq!(read.unwrap(), 0);
+    }
+}